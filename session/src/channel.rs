@@ -0,0 +1,221 @@
+use russh::{client::Msg, ChannelMsg, Sig};
+use tracing::trace;
+
+use crate::{Error, Result};
+
+/// Extended data type used by servers for stderr, as defined in RFC 4254.
+const SSH_EXTENDED_DATA_STDERR: u32 = 1;
+
+/// Size of the terminal, in cells and in pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TermSize {
+    pub columns: u32,
+    pub rows: u32,
+    pub pixel_width: u32,
+    pub pixel_height: u32,
+}
+
+/// How a remote session came to an end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEnd {
+    /// The remote process exited with the given status code.
+    Exited(u32),
+    /// The remote process was killed by a signal.
+    Killed { signal: String, core_dumped: bool },
+    /// The channel was closed without reporting an exit status.
+    Closed,
+    /// The server stopped answering keepalives.
+    ConnectionLost,
+}
+
+impl SessionEnd {
+    /// Whether the session ended without the remote process or the user asking for it.
+    pub fn is_unexpected(&self) -> bool {
+        matches!(self, SessionEnd::ConnectionLost)
+    }
+}
+
+impl std::fmt::Display for SessionEnd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionEnd::Exited(code) => write!(f, "Process exited with code {}", code),
+            SessionEnd::Killed {
+                signal,
+                core_dumped,
+            } => {
+                write!(f, "Process killed by SIG{}", signal)?;
+                if *core_dumped {
+                    write!(f, " (core dumped)")?;
+                }
+                Ok(())
+            }
+            SessionEnd::Closed => write!(f, "Connection closed"),
+            SessionEnd::ConnectionLost => {
+                write!(f, "Connection lost : the server stopped responding")
+            }
+        }
+    }
+}
+
+fn signal_name(sig: &Sig) -> String {
+    match sig {
+        Sig::ABRT => "ABRT".to_owned(),
+        Sig::ALRM => "ALRM".to_owned(),
+        Sig::FPE => "FPE".to_owned(),
+        Sig::HUP => "HUP".to_owned(),
+        Sig::ILL => "ILL".to_owned(),
+        Sig::INT => "INT".to_owned(),
+        Sig::KILL => "KILL".to_owned(),
+        Sig::PIPE => "PIPE".to_owned(),
+        Sig::QUIT => "QUIT".to_owned(),
+        Sig::SEGV => "SEGV".to_owned(),
+        Sig::TERM => "TERM".to_owned(),
+        Sig::USR1 => "USR1".to_owned(),
+        Sig::Custom(name) => name.clone(),
+    }
+}
+
+/// Wait for the reply to the last request sent with `want_reply`.
+pub(crate) async fn wait_reply(channel: &mut russh::Channel<Msg>) -> bool {
+    while let Some(msg) = channel.wait().await {
+        match msg {
+            ChannelMsg::Success => return true,
+            ChannelMsg::Failure => return false,
+            _ => {}
+        }
+    }
+
+    false
+}
+
+/// Bytes that can be sent on `channel` right away.
+///
+/// russh waits for the window of the server by reading the channel itself, and drops whatever
+/// else it reads meanwhile. Sends are kept a byte short of filling the window so it never has to.
+pub(crate) fn writable(channel: &russh::Channel<Msg>) -> usize {
+    channel.writable_packet_size().saturating_sub(1)
+}
+
+/// Send the start of `data` that fits in the window of the server, returning its length.
+pub(crate) async fn send(
+    channel: &mut russh::Channel<Msg>,
+    data: &[u8],
+) -> std::result::Result<usize, russh::Error> {
+    let len = data.len().min(writable(channel));
+    if len > 0 {
+        channel.data(&data[..len]).await?;
+    }
+    Ok(len)
+}
+
+/// What a [`Channel`] received from the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelOutput {
+    Stdout(Vec<u8>),
+    Stderr(Vec<u8>),
+    /// The remote process reported how it ended, the channel may still be open.
+    Exit(SessionEnd),
+    /// The remote process won't write anything more.
+    Eof,
+    /// The channel was closed, by the server or with the connection, nothing follows.
+    Closed,
+    /// The server made room for more input, see [`Channel::write`].
+    Writable,
+}
+
+/// Shell or command running on the server.
+pub struct Channel {
+    inner: russh::Channel<Msg>,
+    closed: bool,
+}
+
+impl Channel {
+    pub(crate) fn new(inner: russh::Channel<Msg>) -> Self {
+        Self {
+            inner,
+            closed: false,
+        }
+    }
+
+    /// Wait for the next output of the channel, [`ChannelOutput::Closed`] once it is closed
+    /// and `None` after that.
+    ///
+    /// A closed channel and a lost connection look the same from here, see
+    /// [`Session::is_closed`](crate::Session::is_closed) to tell them apart.
+    pub async fn next(&mut self) -> Option<ChannelOutput> {
+        if self.closed {
+            return None;
+        }
+
+        loop {
+            // russh forgets the channel as soon as the server closes it, without reporting it.
+            let Some(msg) = self.inner.wait().await else {
+                self.closed = true;
+                return Some(ChannelOutput::Closed);
+            };
+
+            let output = match msg {
+                ChannelMsg::Data { data } => ChannelOutput::Stdout(data.to_vec()),
+                ChannelMsg::ExtendedData { data, ext } if ext == SSH_EXTENDED_DATA_STDERR => {
+                    ChannelOutput::Stderr(data.to_vec())
+                }
+                ChannelMsg::ExtendedData { ext, .. } => {
+                    trace!("ignoring extended data of type {}", ext);
+                    continue;
+                }
+                ChannelMsg::ExitStatus { exit_status } => {
+                    trace!("remote process exited with code {}", exit_status);
+                    ChannelOutput::Exit(SessionEnd::Exited(exit_status))
+                }
+                ChannelMsg::ExitSignal {
+                    signal_name,
+                    core_dumped,
+                    ..
+                } => {
+                    let signal = self::signal_name(&signal_name);
+                    trace!("remote process killed by SIG{}", signal);
+                    ChannelOutput::Exit(SessionEnd::Killed {
+                        signal,
+                        core_dumped,
+                    })
+                }
+                ChannelMsg::Eof => ChannelOutput::Eof,
+                ChannelMsg::Close => {
+                    self.closed = true;
+                    ChannelOutput::Closed
+                }
+                ChannelMsg::WindowAdjusted { .. } if writable(&self.inner) > 0 => {
+                    ChannelOutput::Writable
+                }
+                _ => continue,
+            };
+
+            return Some(output);
+        }
+    }
+
+    /// Send the start of `data` the server has room for and return its length, which is 0
+    /// while its window is full.
+    ///
+    /// This never waits for the window, [`ChannelOutput::Writable`] tells when it opens again.
+    pub async fn write(&mut self, data: &[u8]) -> Result<usize> {
+        send(&mut self.inner, data).await.map_err(Error::Channel)
+    }
+
+    /// Tell the server the pty was resized.
+    pub async fn resize(&mut self, size: TermSize) -> Result<()> {
+        self.inner
+            .window_change(size.columns, size.rows, size.pixel_width, size.pixel_height)
+            .await
+            .map_err(Error::Channel)
+    }
+
+    /// Tell the remote process nothing more will be written.
+    pub async fn eof(&mut self) -> Result<()> {
+        self.inner.eof().await.map_err(Error::Channel)
+    }
+
+    pub async fn close(&mut self) -> Result<()> {
+        self.inner.close().await.map_err(Error::Channel)
+    }
+}
@@ -0,0 +1,358 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use russh::client::Handle;
+use russh_keys::{agent::client::AgentClient, key::PublicKey};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+};
+use tracing::{trace, warn};
+
+use crate::{
+    event::{self, AuthPrompt, EventSender, HostKeyDecision, HostKeyStatus, SessionEvent},
+    forward,
+    proxy::ProxyStream,
+    AuthMethod, ConnectOptions, Error, Result, SessionState,
+};
+
+/// Number of times the user is asked for a password before giving up.
+const MAX_PASSWORD_ATTEMPTS: u32 = 3;
+
+/// Size of the buffer between a jump host channel and the connection going through it.
+const JUMP_BUFFER_SIZE: usize = 64 * 1024;
+
+pub(crate) struct Client {
+    host: String,
+    port: u16,
+    known_hosts: Option<PathBuf>,
+    events: EventSender,
+}
+
+impl Client {
+    fn check_known_hosts(&self, key: &PublicKey) -> std::result::Result<bool, russh_keys::Error> {
+        match &self.known_hosts {
+            Some(path) => russh_keys::check_known_hosts_path(&self.host, self.port, key, path),
+            None => russh_keys::check_known_hosts(&self.host, self.port, key),
+        }
+    }
+
+    fn learn_known_hosts(&self, key: &PublicKey) -> std::result::Result<(), russh_keys::Error> {
+        match &self.known_hosts {
+            Some(path) => russh_keys::learn_known_hosts_path(&self.host, self.port, key, path),
+            None => russh_keys::learn_known_hosts(&self.host, self.port, key),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl russh::client::Handler for Client {
+    type Error = russh::Error;
+
+    async fn check_server_key(
+        self,
+        server_public_key: &PublicKey,
+    ) -> std::result::Result<(Self, bool), Self::Error> {
+        let status = match self.check_known_hosts(server_public_key) {
+            Ok(true) => return Ok((self, true)),
+            Ok(false) => HostKeyStatus::Unknown,
+            Err(russh_keys::Error::KeyChanged { line }) => HostKeyStatus::Changed { line },
+            Err(e) => {
+                warn!("failed to read known hosts : {}", e);
+                HostKeyStatus::Unknown
+            }
+        };
+
+        let decision = event::request(&self.events, |reply| SessionEvent::HostKey {
+            host: self.host.clone(),
+            port: self.port,
+            algorithm: server_public_key.name().to_owned(),
+            fingerprint: server_public_key.fingerprint(),
+            status,
+            reply,
+        })
+        .await
+        .unwrap_or(HostKeyDecision::Reject);
+
+        if decision == HostKeyDecision::AcceptAndSave {
+            if let Err(e) = self.learn_known_hosts(server_public_key) {
+                warn!("failed to save host key : {}", e);
+            }
+        }
+
+        Ok((self, decision != HostKeyDecision::Reject))
+    }
+
+    async fn auth_banner(
+        self,
+        banner: &str,
+        session: russh::client::Session,
+    ) -> std::result::Result<(Self, russh::client::Session), Self::Error> {
+        event::send(&self.events, SessionEvent::Banner(banner.to_owned()));
+        Ok((self, session))
+    }
+}
+
+pub(crate) fn report(events: &EventSender, state: SessionState) {
+    trace!("session state : {:?}", state);
+    event::send(events, SessionEvent::StateChanged(state));
+}
+
+/// Connect to `options` over TCP, or through `proxy_command` when there is one.
+pub(crate) async fn connect(
+    options: &ConnectOptions,
+    proxy_command: Option<&str>,
+    events: &EventSender,
+) -> Result<Handle<Client>> {
+    if let Some(command) = proxy_command {
+        report(events, SessionState::Connecting);
+        let stream = ProxyStream::spawn(&options.expand_proxy_command(command))?;
+        return handshake(options, stream, events).await;
+    }
+
+    let (host, port) = (options.host.as_str(), options.port);
+
+    report(events, SessionState::Resolving);
+    let addrs: Vec<_> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|source| Error::Resolve {
+            host: host.to_owned(),
+            source,
+        })?
+        .collect();
+
+    report(events, SessionState::Connecting);
+    let stream = TcpStream::connect(&addrs[..])
+        .await
+        .map_err(|source| Error::Connect {
+            addr: format!("{}:{}", host, port),
+            source,
+        })?;
+
+    handshake(options, stream, events).await
+}
+
+/// Connect to `options` through a channel of `jump`, which is already authenticated.
+pub(crate) async fn connect_through(
+    jump: &mut Handle<Client>,
+    jump_options: &ConnectOptions,
+    options: &ConnectOptions,
+    events: &EventSender,
+) -> Result<Handle<Client>> {
+    report(events, SessionState::Connecting);
+    let channel = jump
+        .channel_open_direct_tcpip(options.host.as_str(), options.port as u32, "127.0.0.1", 0)
+        .await
+        .map_err(|source| Error::Jump {
+            addr: format!("{}:{}", options.host, options.port),
+            jump: jump_options.to_string(),
+            source,
+        })?;
+
+    let (stream, remote) = tokio::io::duplex(JUMP_BUFFER_SIZE);
+    tokio::spawn(forward::relay(remote, channel));
+
+    handshake(options, stream, events).await
+}
+
+async fn handshake<S>(
+    options: &ConnectOptions,
+    stream: S,
+    events: &EventSender,
+) -> Result<Handle<Client>>
+where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let (host, port) = (options.host.as_str(), options.port);
+
+    let config = russh::client::Config { ..<_>::default() };
+    let config = Arc::new(config);
+
+    // The host key is checked during the handshake.
+    report(events, SessionState::VerifyingHost);
+    let sh = Client {
+        host: host.to_owned(),
+        port,
+        known_hosts: options.known_hosts.clone(),
+        events: events.clone(),
+    };
+    russh::client::connect_stream(config, stream, sh)
+        .await
+        .map_err(|e| match e {
+            russh::Error::UnknownKey => Error::HostKey {
+                host: host.to_owned(),
+            },
+            e => Error::Handshake(e),
+        })
+}
+
+async fn authenticate_with_agent(session: &mut Handle<Client>, username: &str) -> Result<bool> {
+    let mut client = match AgentClient::connect_env().await {
+        Ok(client) => client,
+        Err(e) => {
+            trace!("no ssh agent available : {}", e);
+            return Ok(false);
+        }
+    };
+
+    let identities = match client.request_identities().await {
+        Ok(identities) => identities,
+        Err(e) => {
+            warn!("failed to list agent identities : {}", e);
+            return Ok(false);
+        }
+    };
+
+    for key in identities {
+        trace!("trying {}  {}", key.name(), key.fingerprint());
+        let (c, r) = session.authenticate_future(username, key, client).await;
+
+        client = c;
+
+        let is_auth = r.map_err(|e| auth_error(username, e.to_string()))?;
+
+        trace!("is auth successful : {}", is_auth);
+
+        if is_auth {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+fn auth_error(username: &str, reason: impl Into<String>) -> Error {
+    Error::Auth {
+        user: username.to_owned(),
+        reason: reason.into(),
+    }
+}
+
+pub(crate) async fn authenticate(
+    session: &mut Handle<Client>,
+    username: &str,
+    method: &AuthMethod,
+    events: &EventSender,
+) -> Result<()> {
+    match method {
+        AuthMethod::Auto => {
+            if authenticate_with_agent(session, username).await? {
+                return Ok(());
+            }
+            authenticate_with_password(session, username, events).await
+        }
+        AuthMethod::Agent => {
+            if authenticate_with_agent(session, username).await? {
+                return Ok(());
+            }
+            Err(auth_error(
+                username,
+                "no identity of the agent was accepted",
+            ))
+        }
+        AuthMethod::Password => authenticate_with_password(session, username, events).await,
+        AuthMethod::IdentityFile(path) => {
+            authenticate_with_key(session, username, path, events).await
+        }
+    }
+}
+
+async fn authenticate_with_key(
+    session: &mut Handle<Client>,
+    username: &str,
+    path: &Path,
+    events: &EventSender,
+) -> Result<()> {
+    let load_error = |e: russh_keys::Error| {
+        auth_error(
+            username,
+            format!("failed to load {} : {}", path.display(), e),
+        )
+    };
+
+    let key = match russh_keys::load_secret_key(path, None) {
+        Ok(key) => key,
+        Err(russh_keys::Error::KeyIsEncrypted) => {
+            let mut attempt = 0;
+            loop {
+                let passphrase = event::request(events, |reply| SessionEvent::Prompt {
+                    prompt: AuthPrompt::Passphrase {
+                        path: path.to_owned(),
+                        attempt,
+                    },
+                    reply,
+                })
+                .await
+                .flatten();
+
+                let Some(passphrase) = passphrase else {
+                    return Err(auth_error(username, "cancelled by the user"));
+                };
+
+                // A wrong passphrase makes the decrypted key unreadable, there is no dedicated error.
+                match russh_keys::load_secret_key(path, Some(&passphrase)) {
+                    Ok(key) => break key,
+                    Err(e) if attempt + 1 >= MAX_PASSWORD_ATTEMPTS => return Err(load_error(e)),
+                    Err(e) => {
+                        trace!("failed to decrypt {} : {}", path.display(), e);
+                        attempt += 1;
+                    }
+                }
+            }
+        }
+        Err(e) => return Err(load_error(e)),
+    };
+
+    let is_auth = session
+        .authenticate_publickey(username, Arc::new(key))
+        .await
+        .map_err(|e| auth_error(username, e.to_string()))?;
+
+    trace!("is key auth successful : {}", is_auth);
+
+    if is_auth {
+        Ok(())
+    } else {
+        Err(auth_error(
+            username,
+            format!("the key {} was not accepted", path.display()),
+        ))
+    }
+}
+
+async fn authenticate_with_password(
+    session: &mut Handle<Client>,
+    username: &str,
+    events: &EventSender,
+) -> Result<()> {
+    for attempt in 0..MAX_PASSWORD_ATTEMPTS {
+        let password = event::request(events, |reply| SessionEvent::Prompt {
+            prompt: AuthPrompt::Password {
+                user: username.to_owned(),
+                attempt,
+            },
+            reply,
+        })
+        .await
+        .flatten();
+
+        let Some(password) = password else {
+            return Err(auth_error(username, "cancelled by the user"));
+        };
+
+        let is_auth = session
+            .authenticate_password(username, password)
+            .await
+            .map_err(|e| auth_error(username, e.to_string()))?;
+
+        trace!("is password auth successful : {}", is_auth);
+
+        if is_auth {
+            return Ok(());
+        }
+    }
+
+    Err(auth_error(username, "no identity or password was accepted"))
+}
@@ -0,0 +1,105 @@
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+
+    #[error("Failed to resolve {host} : {source}")]
+    Resolve {
+        host: String,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to connect to {addr} : {source}")]
+    Connect {
+        addr: String,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to run the proxy command {command} : {source}")]
+    ProxyCommand {
+        command: String,
+        source: std::io::Error,
+    },
+
+    #[error("SSH handshake failed : {0}")]
+    Handshake(#[source] russh::Error),
+
+    #[error("The host key of {host} was not accepted")]
+    HostKey { host: String },
+
+    #[error("Authentication as {user} failed : {reason}")]
+    Auth { user: String, reason: String },
+
+    #[error("Session channel failed : {0}")]
+    Channel(#[source] russh::Error),
+
+    #[error("Failed to allocate a pty : {reason}")]
+    Pty { reason: String },
+
+    #[error("The server refused to start a shell")]
+    Shell,
+
+    #[error("The server refused to run {command}")]
+    Exec { command: String },
+
+    #[error("Failed to forward a connection to {addr} : {source}")]
+    Forward {
+        addr: String,
+        #[source]
+        source: russh::Error,
+    },
+
+    #[error("Failed to disconnect : {0}")]
+    Disconnect(#[source] russh::Error),
+
+    #[error("{0} is not a valid destination, expected [user@]host[:port]")]
+    InvalidTarget(String),
+
+    #[error("Failed to reach {addr} through {jump} : {source}")]
+    Jump {
+        addr: String,
+        jump: String,
+        #[source]
+        source: russh::Error,
+    },
+}
+
+impl Error {
+    /// Short description of the stage that failed, suitable for a page title.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Error::IO(_) => "Input/Output Error",
+            Error::Resolve { .. } => "Unknown Host",
+            Error::Connect { .. } => "Connection Failed",
+            Error::ProxyCommand { .. } => "Proxy Command Failed",
+            Error::Handshake(_) => "Handshake Failed",
+            Error::HostKey { .. } => "Host Key Rejected",
+            Error::Auth { .. } => "Authentication Failed",
+            Error::Channel(_) => "Session Failed",
+            Error::Pty { .. } => "Terminal Unavailable",
+            Error::Shell => "Shell Refused",
+            Error::Exec { .. } => "Command Refused",
+            Error::Forward { .. } => "Forwarding Failed",
+            Error::Disconnect(_) => "Disconnection Failed",
+            Error::InvalidTarget(_) => "Invalid Destination",
+            Error::Jump { .. } => "Jump Host Failed",
+        }
+    }
+
+    /// Whether trying again may succeed without the user changing anything, because the
+    /// network or the server failed rather than the user's choices or the configuration.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Error::IO(_)
+                | Error::Resolve { .. }
+                | Error::Connect { .. }
+                | Error::Handshake(_)
+                | Error::Channel(_)
+                | Error::Disconnect(_)
+                | Error::Jump { .. }
+        )
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use tokio::sync::{mpsc, oneshot};
+use tracing::trace;
+
+use crate::{Result, SessionEnd, SessionState};
+
+pub type EventSender = mpsc::UnboundedSender<SessionEvent>;
+pub type EventReceiver = mpsc::UnboundedReceiver<SessionEvent>;
+
+/// Answer to a request the session is waiting on.
+///
+/// Dropping it without sending anything is the same as refusing the request.
+#[derive(Debug)]
+pub struct Reply<T>(oneshot::Sender<T>);
+
+impl<T> Reply<T> {
+    pub fn send(self, value: T) {
+        if self.0.send(value).is_err() {
+            trace!("the session stopped waiting for the reply");
+        }
+    }
+}
+
+/// What is known about the host key offered by the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostKeyStatus {
+    /// The host is not in `known_hosts`.
+    Unknown,
+    /// The host is in `known_hosts` with another key, at the given line.
+    Changed { line: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostKeyDecision {
+    Reject,
+    AcceptOnce,
+    AcceptAndSave,
+}
+
+/// Secret the session needs from the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthPrompt {
+    /// Password of `user`, `attempt` starts at 0.
+    Password { user: String, attempt: u32 },
+    /// Passphrase of the private key stored at `path`, `attempt` starts at 0.
+    Passphrase { path: PathBuf, attempt: u32 },
+}
+
+/// Transfer counters of a session, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionStats {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+/// Event sent by a session to the user interface, or whatever drives it.
+#[derive(Debug)]
+pub enum SessionEvent {
+    StateChanged(SessionState),
+    /// Message sent by the server before authentication.
+    Banner(String),
+    /// The server offered a host key that is not trusted yet.
+    HostKey {
+        host: String,
+        port: u16,
+        algorithm: String,
+        fingerprint: String,
+        status: HostKeyStatus,
+        reply: Reply<HostKeyDecision>,
+    },
+    /// The session needs a secret, `None` cancels the authentication.
+    Prompt {
+        prompt: AuthPrompt,
+        reply: Reply<Option<String>>,
+    },
+    /// The server refused these environment variables.
+    EnvRejected(Vec<String>),
+    /// The remote process reported how it ended, the channel may still be open.
+    Exit(SessionEnd),
+    Stats(SessionStats),
+    /// Last event of a session.
+    Finished(Result<SessionEnd>),
+}
+
+pub(crate) fn send(events: &EventSender, event: SessionEvent) {
+    if events.send(event).is_err() {
+        trace!("nobody is listening to the session events");
+    }
+}
+
+/// Send a request to the user interface and wait for its reply, `None` if it was dropped.
+pub(crate) async fn request<T>(
+    events: &EventSender,
+    event: impl FnOnce(Reply<T>) -> SessionEvent,
+) -> Option<T> {
+    let (sender, receiver) = oneshot::channel();
+    send(events, event(Reply(sender)));
+    receiver.await.ok()
+}
@@ -0,0 +1,156 @@
+use std::{collections::VecDeque, net::SocketAddr};
+
+use russh::{client::Msg, ChannelMsg};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpListener,
+    task::JoinHandle,
+};
+use tracing::{error, trace, warn};
+
+use crate::{channel, Result, Session};
+
+/// Size of the buffer used to read forwarded connections.
+const FORWARD_BUFFER_SIZE: usize = 32 * 1024;
+
+/// Local port forwarded through a [`Session`], stopped when dropped.
+#[derive(Debug)]
+pub struct Forward {
+    local_addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl Forward {
+    /// Address the forwarded port listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Drop for Forward {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+pub(crate) fn spawn(
+    session: Session,
+    listener: TcpListener,
+    host: String,
+    port: u16,
+) -> Result<Forward> {
+    let local_addr = listener.local_addr()?;
+
+    let task = tokio::spawn(async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    error!("failed to accept a forwarded connection : {}", e);
+                    break;
+                }
+            };
+
+            trace!("forwarding {} to {}:{}", peer, host, port);
+            match session.open_direct_tcpip(&host, port, peer).await {
+                Ok(channel) => {
+                    tokio::spawn(relay(stream, channel));
+                }
+                Err(e) => warn!("{}", e),
+            }
+        }
+    });
+
+    Ok(Forward { local_addr, task })
+}
+
+/// Copy data both ways between a stream and a channel, until either is closed.
+///
+/// The channel is read all along, data for the stream is queued while the stream is slow and
+/// data for the channel waits for room in the window of the server.
+pub(crate) async fn relay<S>(stream: S, mut channel: russh::Channel<Msg>)
+where
+    S: AsyncRead + AsyncWrite + Send + Unpin,
+{
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    let mut buf = vec![0u8; FORWARD_BUFFER_SIZE];
+    // Read from the stream, waiting for room in the window of the server.
+    let mut input: Vec<u8> = Vec::new();
+    // Received on the channel, waiting to be written to the stream.
+    let mut output: VecDeque<u8> = VecDeque::new();
+    let (mut local_eof, mut eof_sent) = (false, false);
+    let (mut remote_eof, mut shut_down, mut remote_closed) = (false, false, false);
+
+    loop {
+        if !input.is_empty() {
+            match channel::send(&mut channel, &input).await {
+                Ok(sent) => {
+                    input.drain(..sent);
+                }
+                Err(e) => {
+                    warn!("failed to send forwarded data : {}", e);
+                    break;
+                }
+            }
+        }
+
+        if local_eof && input.is_empty() && !eof_sent {
+            eof_sent = true;
+            if let Err(e) = channel.eof().await {
+                warn!("failed to send eof : {}", e);
+                break;
+            }
+        }
+
+        if output.is_empty() {
+            if remote_closed {
+                break;
+            }
+            if remote_eof && !shut_down {
+                shut_down = true;
+                if let Err(e) = writer.shutdown().await {
+                    trace!("failed to shut down forwarded stream : {}", e);
+                }
+            }
+        }
+
+        tokio::select! {
+            res = reader.read(&mut buf), if input.is_empty() && !local_eof => {
+                match res {
+                    Ok(0) => local_eof = true,
+                    Ok(n) => input.extend_from_slice(&buf[..n]),
+                    Err(e) => {
+                        warn!("failed to read forwarded stream : {}", e);
+                        break;
+                    }
+                }
+            }
+
+            res = writer.write(output.as_slices().0), if !output.is_empty() => {
+                match res {
+                    Ok(n) => {
+                        output.drain(..n);
+                    }
+                    Err(e) => {
+                        warn!("failed to write forwarded stream : {}", e);
+                        break;
+                    }
+                }
+            }
+
+            msg = channel.wait(), if !remote_closed => {
+                match msg {
+                    Some(ChannelMsg::Data { ref data }) => output.extend(data.iter()),
+                    Some(ChannelMsg::Eof) => remote_eof = true,
+                    Some(ChannelMsg::Close) | None => remote_closed = true,
+                    // Window adjustments are applied by `wait`, the loop sends what now fits.
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if let Err(e) = channel.close().await {
+        trace!("failed to close forwarded channel : {}", e);
+    }
+}
@@ -0,0 +1,34 @@
+//! SSH sessions as Flatline opens them, without any user interface.
+//!
+//! A [`Session`] is established with [`Session::connect`], which reports its progress and
+//! asks for host key approvals and passwords through [`SessionEvent`]s. Shells and commands
+//! then run on [`Channel`]s, and local ports are forwarded with [`Session::forward_local`].
+//!
+//! [`run_terminal`] drives a whole interactive session over a local pty, as the Flatline
+//! panes do.
+
+mod channel;
+mod client;
+mod error;
+mod event;
+mod forward;
+mod options;
+mod proxy;
+pub mod pty;
+mod session;
+mod terminal;
+
+pub use channel::{Channel, ChannelOutput, SessionEnd, TermSize};
+pub use error::{Error, Result};
+pub use event::{
+    AuthPrompt, EventReceiver, EventSender, HostKeyDecision, HostKeyStatus, Reply, SessionEvent,
+    SessionStats,
+};
+pub use forward::Forward;
+pub use options::{
+    percent_decode, AuthMethod, ConnectOptions, SessionOptions, DEFAULT_PORT, DEFAULT_SEND_ENV,
+    DEFAULT_TERMINAL_MODES,
+};
+pub use russh::Pty;
+pub use session::{Session, SessionState};
+pub use terminal::{run_terminal, Control};
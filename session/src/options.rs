@@ -0,0 +1,290 @@
+use std::{fmt, path::PathBuf, str::FromStr, time::Duration};
+
+use russh::Pty;
+
+use crate::Error;
+
+/// Port used when none is given.
+pub const DEFAULT_PORT: u16 = 22;
+
+/// Terminal modes sent with the pty request when none are configured.
+pub const DEFAULT_TERMINAL_MODES: &[(Pty, u32)] = &[
+    (Pty::VINTR, 0x03),
+    (Pty::VQUIT, 0x1c),
+    (Pty::VERASE, 0x7f),
+    (Pty::VKILL, 0x15),
+    (Pty::VEOF, 0x04),
+    (Pty::VSUSP, 0x1a),
+    (Pty::ICRNL, 1),
+    (Pty::IXON, 1),
+    (Pty::IUTF8, 1),
+    (Pty::ISIG, 1),
+    (Pty::ICANON, 1),
+    (Pty::IEXTEN, 1),
+    (Pty::ECHO, 1),
+    (Pty::ECHOE, 1),
+    (Pty::ECHOK, 1),
+    (Pty::ECHOCTL, 1),
+    (Pty::ECHOKE, 1),
+    (Pty::OPOST, 1),
+    (Pty::ONLCR, 1),
+    (Pty::CS8, 1),
+    (Pty::TTY_OP_ISPEED, 38400),
+    (Pty::TTY_OP_OSPEED, 38400),
+];
+
+/// Local environment variables forwarded to the server when none are configured,
+/// in the same form as OpenSSH's `SendEnv`.
+pub const DEFAULT_SEND_ENV: &[&str] = &["LANG", "LC_*"];
+
+/// How to authenticate once connected.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum AuthMethod {
+    /// The identities of the agent, then a password.
+    #[default]
+    Auto,
+    /// The identities of the agent only.
+    Agent,
+    Password,
+    /// The private key stored in the given file, its passphrase is asked if it is encrypted.
+    IdentityFile(PathBuf),
+}
+
+/// Where to connect and as whom.
+///
+/// Parsing accepts `[user@]host[:port]`, with IPv6 addresses between brackets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectOptions {
+    pub host: String,
+    pub port: u16,
+    /// User to log in as, `None` for `$SSH_USERNAME` or the local login name.
+    pub user: Option<String>,
+    pub auth: AuthMethod,
+    /// Hosts to go through, in order, like OpenSSH's `ProxyJump`.
+    pub jump_hosts: Vec<ConnectOptions>,
+    /// File the host keys are checked against and saved to, `None` for `~/.ssh/known_hosts`.
+    pub known_hosts: Option<PathBuf>,
+    /// Command run with `sh -c` whose input and output carry the connection, like OpenSSH's
+    /// `ProxyCommand`, see [`ConnectOptions::expand_proxy_command`].
+    ///
+    /// It reaches the first jump host when there are some, those of the jump hosts are ignored.
+    pub proxy_command: Option<String>,
+}
+
+impl ConnectOptions {
+    pub fn new(host: impl Into<String>, port: u16) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            user: None,
+            auth: AuthMethod::default(),
+            jump_hosts: Vec::new(),
+            known_hosts: None,
+            proxy_command: None,
+        }
+    }
+
+    /// User to log in as, once the defaults are applied.
+    pub fn user(&self) -> String {
+        if let Some(user) = &self.user {
+            return user.clone();
+        }
+
+        std::env::var("SSH_USERNAME").unwrap_or_else(|_| local_user())
+    }
+
+    /// `command` with `%h`, `%p` and `%r` replaced by the host, port and user of these options,
+    /// and `%%` by `%`. Other sequences are left as they are.
+    pub fn expand_proxy_command(&self, command: &str) -> String {
+        let mut expanded = String::with_capacity(command.len());
+        let mut chars = command.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                expanded.push(c);
+                continue;
+            }
+
+            match chars.next() {
+                Some('h') => expanded.push_str(&self.host),
+                Some('p') => expanded.push_str(&self.port.to_string()),
+                Some('r') => expanded.push_str(&self.user()),
+                Some('%') => expanded.push('%'),
+                Some(other) => {
+                    expanded.push('%');
+                    expanded.push(other);
+                }
+                None => expanded.push('%'),
+            }
+        }
+        expanded
+    }
+}
+
+/// Parses `[user@]host[:port]`, or an `ssh://[user@]host[:port]` URI.
+impl FromStr for ConnectOptions {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidTarget(s.to_owned());
+
+        let s = s.trim();
+        let (target, is_uri) = match s.get(..6) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("ssh://") => {
+                (s[6..].strip_suffix('/').unwrap_or(&s[6..]), true)
+            }
+            _ => (s, false),
+        };
+
+        let (user, address) = match target.rsplit_once('@') {
+            Some(("", _)) => return Err(invalid()),
+            // The user info of a URI may carry parameters, such as a host key fingerprint.
+            Some((user, address)) if is_uri => {
+                let user = user.split(';').next().unwrap_or_default();
+                let user = percent_decode(user).ok_or_else(invalid)?;
+                (Some(user), address)
+            }
+            Some((user, address)) => (Some(user.to_owned()), address),
+            None => (None, target),
+        };
+        if user.as_deref() == Some("") {
+            return Err(invalid());
+        }
+
+        let (host, port) = match address.strip_prefix('[') {
+            Some(address) => {
+                let (host, rest) = address.split_once(']').ok_or_else(invalid)?;
+                match rest {
+                    "" => (host, None),
+                    rest => (host, Some(rest.strip_prefix(':').ok_or_else(invalid)?)),
+                }
+            }
+            None => match address.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (address, None),
+            },
+        };
+
+        if host.is_empty() || host.contains(|c: char| c.is_whitespace() || c == '/') {
+            return Err(invalid());
+        }
+
+        let port = match port {
+            Some(port) => port
+                .parse::<u16>()
+                .ok()
+                .filter(|port| *port != 0)
+                .ok_or_else(invalid)?,
+            None => DEFAULT_PORT,
+        };
+
+        Ok(Self {
+            user,
+            ..Self::new(host, port)
+        })
+    }
+}
+
+/// Decode the `%XX` escapes of a URI component, `None` if an escape is truncated or the result
+/// isn't valid UTF-8.
+pub fn percent_decode(s: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+impl fmt::Display for ConnectOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(user) = &self.user {
+            write!(f, "{}@", user)?;
+        }
+
+        let bracketed = self.host.contains(':');
+        match (bracketed, self.port) {
+            (false, DEFAULT_PORT) => write!(f, "{}", self.host),
+            (true, DEFAULT_PORT) => write!(f, "[{}]", self.host),
+            (false, port) => write!(f, "{}:{}", self.host, port),
+            (true, port) => write!(f, "[{}]:{}", self.host, port),
+        }
+    }
+}
+
+fn local_user() -> String {
+    let login = unsafe { libc::getlogin() };
+    if login.is_null() {
+        // There is no controlling terminal, when started from a desktop launcher for instance.
+        return std::env::var("USER").unwrap_or_default();
+    }
+
+    unsafe { std::ffi::CStr::from_ptr(login) }
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Settings of an interactive session.
+#[derive(Debug, Clone)]
+pub struct SessionOptions {
+    /// Value of `TERM` sent with the pty request.
+    pub term: String,
+    /// Environment variables set on the remote session, applied after `send_env`.
+    pub env: Vec<(String, String)>,
+    /// Names of local environment variables to forward, a trailing `*` matches any suffix.
+    pub send_env: Vec<String>,
+    /// Terminal modes sent with the pty request.
+    pub terminal_modes: Vec<(Pty, u32)>,
+    /// Color used to tint stderr output in the terminal, as `(r, g, b)`.
+    pub stderr_color: Option<(u8, u8, u8)>,
+    /// Delay without any traffic from the server before probing it, like `ServerAliveInterval`.
+    pub keepalive_interval: Option<Duration>,
+    /// Number of unanswered probes before the connection is considered lost, like `ServerAliveCountMax`.
+    pub keepalive_count_max: u32,
+}
+
+impl Default for SessionOptions {
+    fn default() -> Self {
+        Self {
+            term: "xterm-256color".to_owned(),
+            env: vec![("COLORTERM".to_owned(), "truecolor".to_owned())],
+            send_env: DEFAULT_SEND_ENV.iter().map(|s| s.to_string()).collect(),
+            terminal_modes: DEFAULT_TERMINAL_MODES.to_vec(),
+            stderr_color: None,
+            keepalive_interval: Some(Duration::from_secs(15)),
+            keepalive_count_max: 3,
+        }
+    }
+}
+
+impl SessionOptions {
+    /// Environment sent to the server : the forwarded local variables, then the configured ones.
+    pub(crate) fn environment(&self) -> Vec<(String, String)> {
+        let matches = |name: &str| {
+            self.send_env
+                .iter()
+                .any(|pattern| match pattern.strip_suffix('*') {
+                    Some(prefix) => name.starts_with(prefix),
+                    None => name == pattern,
+                })
+        };
+
+        let mut env: Vec<(String, String)> = std::env::vars().filter(|(k, _)| matches(k)).collect();
+        env.sort();
+
+        for (name, value) in &self.env {
+            env.retain(|(k, _)| k != name);
+            env.push((name.clone(), value.clone()));
+        }
+
+        env
+    }
+}
@@ -0,0 +1,81 @@
+use std::{
+    io,
+    pin::Pin,
+    process::Stdio,
+    task::{Context, Poll},
+};
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    process::{Child, ChildStdin, ChildStdout, Command},
+};
+use tracing::trace;
+
+use crate::{Error, Result};
+
+/// Input and output of a running proxy command, which is killed when the stream is dropped.
+pub(crate) struct ProxyStream {
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: ChildStdout,
+}
+
+impl ProxyStream {
+    /// Run `command` with `sh -c`, its error output goes to ours as with OpenSSH.
+    pub(crate) fn spawn(command: &str) -> Result<Self> {
+        trace!("running proxy command {}", command);
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|source| Error::ProxyCommand {
+                command: command.to_owned(),
+                source,
+            })?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .expect("the input of the command is piped");
+        let stdout = child
+            .stdout
+            .take()
+            .expect("the output of the command is piped");
+        Ok(Self {
+            _child: child,
+            stdin,
+            stdout,
+        })
+    }
+}
+
+impl AsyncRead for ProxyStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stdout).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for ProxyStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stdin).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stdin).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stdin).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,153 @@
+//! Local pseudo terminals, the end of a session the user interacts with.
+
+use std::{
+    io,
+    mem::MaybeUninit,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use tokio::io::{unix::AsyncFd, AsyncRead, AsyncWrite, ReadBuf};
+
+/// Open a pty, returning its master end in raw mode and its slave end in non blocking mode.
+///
+/// The master is given to the terminal emulator, the slave to [`run_terminal`](crate::run_terminal).
+pub fn open_pty() -> io::Result<(OwnedFd, OwnedFd)> {
+    unsafe {
+        // Open the master pty
+        let master_pty = libc::posix_openpt(libc::O_RDWR).into_result()?;
+
+        // Configure the master pty : set it into raw mod so every sequences written by the user are send to the server.
+        let mut settings = MaybeUninit::<libc::termios>::uninit();
+        libc::tcgetattr(master_pty, settings.as_mut_ptr()).into_result()?;
+        libc::cfmakeraw(settings.as_mut_ptr());
+        libc::tcsetattr(master_pty, libc::TCSANOW, settings.as_mut_ptr()).into_result()?;
+
+        // Configuration is over, allow opening of slave pty
+        libc::grantpt(master_pty).into_result()?;
+        libc::unlockpt(master_pty).into_result()?;
+
+        // Get slave pty path
+        let pts_name = libc::ptsname(master_pty);
+        if pts_name.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+
+        // Open slave pty handle
+        let slave_pty = libc::open(pts_name, libc::O_RDWR).into_result()?;
+
+        // Set slave pty to non blocking
+        let flags = libc::fcntl(slave_pty, libc::F_GETFL, 0).into_result()?;
+        libc::fcntl(slave_pty, libc::F_SETFL, flags | libc::O_NONBLOCK).into_result()?;
+
+        Ok((
+            OwnedFd::from_raw_fd(master_pty),
+            OwnedFd::from_raw_fd(slave_pty),
+        ))
+    }
+}
+
+trait LibcResultExt: Sized {
+    fn into_result(self) -> Result<Self, io::Error>;
+}
+
+impl LibcResultExt for libc::c_int {
+    fn into_result(self) -> Result<Self, io::Error> {
+        if self < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+impl LibcResultExt for libc::ssize_t {
+    fn into_result(self) -> Result<Self, io::Error> {
+        if self < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+/// Non blocking pty handle, usable with tokio's `AsyncReadExt` and `AsyncWriteExt`.
+///
+/// Reads and writes wait for the pty to be ready, so a pty that is not drained
+/// slows down the writer instead of losing data.
+pub struct AsyncPty(AsyncFd<OwnedFd>);
+
+impl AsyncPty {
+    /// Wrap `fd`, which must be in non blocking mode.
+    pub fn new(fd: OwnedFd) -> io::Result<Self> {
+        Ok(Self(AsyncFd::new(fd)?))
+    }
+}
+
+impl AsyncRead for AsyncPty {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        loop {
+            let mut guard = ready!(self.0.poll_read_ready(cx))?;
+
+            let unfilled = buf.initialize_unfilled();
+            match guard.try_io(|fd| {
+                let res = unsafe {
+                    libc::read(
+                        fd.as_raw_fd(),
+                        unfilled.as_mut_ptr() as *mut libc::c_void,
+                        unfilled.len(),
+                    )
+                };
+                res.into_result().map(|n| n as usize)
+            }) {
+                Ok(Ok(n)) => {
+                    buf.advance(n);
+                    return Poll::Ready(Ok(()));
+                }
+                // The other end of the pty was closed, report it as the end of the stream.
+                Ok(Err(e)) if e.raw_os_error() == Some(libc::EIO) => return Poll::Ready(Ok(())),
+                Ok(Err(e)) => return Poll::Ready(Err(e)),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+}
+
+impl AsyncWrite for AsyncPty {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            let mut guard = ready!(self.0.poll_write_ready(cx))?;
+
+            match guard.try_io(|fd| {
+                let res = unsafe {
+                    libc::write(
+                        fd.as_raw_fd(),
+                        buf.as_ptr() as *const libc::c_void,
+                        buf.len(),
+                    )
+                };
+                res.into_result().map(|n| n as usize)
+            }) {
+                Ok(res) => return Poll::Ready(res),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
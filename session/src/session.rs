@@ -0,0 +1,229 @@
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use russh::client::{Handle, Msg};
+use tokio::{net::TcpListener, sync::Mutex};
+use tracing::{trace, warn};
+
+use crate::{
+    channel::wait_reply,
+    client::{self, Client},
+    event::{self, EventSender, SessionEvent},
+    forward::{self, Forward},
+    Channel, ConnectOptions, Error, Result, SessionOptions, TermSize,
+};
+
+/// Progress of a session, reported while it is established.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionState {
+    Resolving,
+    Connecting,
+    VerifyingHost,
+    Authenticating,
+    Connected,
+}
+
+/// Authenticated connection to a server, shared by the channels opened on it.
+///
+/// Clones refer to the same connection.
+#[derive(Clone)]
+pub struct Session {
+    handle: Arc<Mutex<Handle<Client>>>,
+    /// Connections to the jump hosts, which carry the main one.
+    _jumps: Arc<Vec<Handle<Client>>>,
+    events: EventSender,
+}
+
+impl Session {
+    /// Connect and authenticate, reporting the progress on `events`.
+    ///
+    /// The host key approval and the passwords are requested on `events` too, a request
+    /// dropped without a reply is refused. Jump hosts are connected to in order, each through
+    /// the previous one, and go through the same steps.
+    pub async fn connect(options: &ConnectOptions, events: EventSender) -> Result<Self> {
+        let mut jumps: Vec<Handle<Client>> = Vec::new();
+        let mut previous: Option<&ConnectOptions> = None;
+
+        for hop in options.jump_hosts.iter().chain(std::iter::once(options)) {
+            let mut handle = match (jumps.last_mut(), previous) {
+                (Some(jump), Some(jump_options)) => {
+                    client::connect_through(jump, jump_options, hop, &events).await?
+                }
+                _ => client::connect(hop, options.proxy_command.as_deref(), &events).await?,
+            };
+
+            let username = hop.user();
+            trace!("username for {} is {}", hop.host, username);
+
+            client::report(&events, SessionState::Authenticating);
+            client::authenticate(&mut handle, &username, &hop.auth, &events).await?;
+
+            jumps.push(handle);
+            previous = Some(hop);
+        }
+
+        let handle = jumps.pop().expect("the destination is always connected to");
+
+        Ok(Self {
+            handle: Arc::new(Mutex::new(handle)),
+            _jumps: Arc::new(jumps),
+            events,
+        })
+    }
+
+    async fn open_session(&self) -> Result<russh::Channel<Msg>> {
+        self.handle
+            .lock()
+            .await
+            .channel_open_session()
+            .await
+            .map_err(Error::Channel)
+    }
+
+    /// Open an interactive shell on a pty of the given size.
+    ///
+    /// Environment variables refused by the server are reported with [`SessionEvent::EnvRejected`].
+    pub async fn open_shell(&self, options: &SessionOptions, size: TermSize) -> Result<Channel> {
+        let mut channel = self.open_session().await?;
+
+        channel
+            .request_pty(
+                true,
+                &options.term,
+                size.columns,
+                size.rows,
+                size.pixel_width,
+                size.pixel_height,
+                &options.terminal_modes,
+            )
+            .await
+            .map_err(|e| Error::Pty {
+                reason: e.to_string(),
+            })?;
+
+        if !wait_reply(&mut channel).await {
+            return Err(Error::Pty {
+                reason: "the server rejected the request".to_owned(),
+            });
+        }
+
+        let mut rejected = Vec::new();
+        for (name, value) in options.environment() {
+            let accepted = match channel.set_env(true, &name, &value).await {
+                Ok(()) => wait_reply(&mut channel).await,
+                Err(e) => {
+                    warn!("failed to send {} : {}", name, e);
+                    false
+                }
+            };
+
+            if !accepted {
+                trace!("server rejected {}", name);
+                rejected.push(name);
+            }
+        }
+
+        if !rejected.is_empty() {
+            event::send(&self.events, SessionEvent::EnvRejected(rejected));
+        }
+
+        channel.request_shell(true).await.map_err(Error::Channel)?;
+
+        if !wait_reply(&mut channel).await {
+            return Err(Error::Shell);
+        }
+
+        Ok(Channel::new(channel))
+    }
+
+    /// Run `command` on the server, without a pty.
+    pub async fn exec(&self, command: &str) -> Result<Channel> {
+        let mut channel = self.open_session().await?;
+
+        channel.exec(true, command).await.map_err(Error::Channel)?;
+
+        if !wait_reply(&mut channel).await {
+            return Err(Error::Exec {
+                command: command.to_owned(),
+            });
+        }
+
+        Ok(Channel::new(channel))
+    }
+
+    /// Forward the connections accepted by `listener` to `host:port`, as seen from the server.
+    ///
+    /// Forwarding stops when the returned [`Forward`] is dropped, the connections already
+    /// accepted go on until they are closed.
+    pub fn forward_local(
+        &self,
+        listener: TcpListener,
+        host: impl Into<String>,
+        port: u16,
+    ) -> Result<Forward> {
+        forward::spawn(self.clone(), listener, host.into(), port)
+    }
+
+    pub(crate) async fn open_direct_tcpip(
+        &self,
+        host: &str,
+        port: u16,
+        originator: SocketAddr,
+    ) -> Result<russh::Channel<Msg>> {
+        self.handle
+            .lock()
+            .await
+            .channel_open_direct_tcpip(
+                host,
+                port as u32,
+                originator.ip().to_string(),
+                originator.port() as u32,
+            )
+            .await
+            .map_err(|source| Error::Forward {
+                addr: format!("{}:{}", host, port),
+                source,
+            })
+    }
+
+    /// Check that the server is still responding, by opening and closing a session channel.
+    ///
+    /// russh doesn't report the reply to a global request such as `keepalive@openssh.com`, a
+    /// channel open is the only exchange whose answer is observable from here. The answer is all
+    /// that matters : a server at its `MaxSessions` limit refuses the channel, which still proves
+    /// it is alive and doesn't count as a miss. Probes are only sent after a period without any
+    /// traffic, so a busy session never opens one.
+    pub async fn probe(&self, timeout: Duration) -> bool {
+        match tokio::time::timeout(timeout, self.open_session()).await {
+            Ok(Ok(probe)) => {
+                if let Err(e) = probe.close().await {
+                    warn!("failed to close keepalive channel : {}", e);
+                }
+                true
+            }
+            Ok(Err(Error::Channel(russh::Error::ChannelOpenFailure(reason)))) => {
+                trace!("keepalive channel refused : {:?}", reason);
+                true
+            }
+            Ok(Err(e)) => {
+                warn!("keepalive failed : {}", e);
+                false
+            }
+            Err(_elapsed) => false,
+        }
+    }
+
+    /// Whether the connection was closed, by either end or because it was lost.
+    pub async fn is_closed(&self) -> bool {
+        self.handle.lock().await.is_closed()
+    }
+
+    /// Disconnect from the server, which closes every channel.
+    pub async fn close(&self) -> Result<()> {
+        self.handle
+            .lock()
+            .await
+            .disconnect(russh::Disconnect::ByApplication, "", "")
+            .await
+            .map_err(Error::Disconnect)
+    }
+}
@@ -0,0 +1,410 @@
+use std::{os::fd::OwnedFd, time::Duration};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt, WriteHalf},
+    sync::{mpsc, watch},
+    task::JoinHandle,
+    time::{Instant, Interval},
+};
+use tracing::{error, trace, warn};
+
+use crate::{
+    client,
+    event::{self, EventSender, SessionEvent, SessionStats},
+    pty::AsyncPty,
+    Channel, ChannelOutput, ConnectOptions, Error, Result, Session, SessionEnd, SessionOptions,
+    SessionState, TermSize,
+};
+
+/// Size of the buffer used to read the pty.
+const PTY_BUFFER_SIZE: usize = 32 * 1024;
+
+/// Chunks of output waiting for the pty, beyond which the channel isn't read anymore.
+const PTY_QUEUE_SIZE: usize = 16;
+
+/// Delay between two [`SessionEvent::Stats`].
+const STATS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Longest wait for the server to acknowledge a disconnection asked with [`Control::Close`].
+const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Command sent to a running [`run_terminal`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    /// Close the channel and disconnect.
+    Close,
+}
+
+async fn keepalive_tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Longest control sequence kept across chunks of stdout, longer ones are ignored.
+const MAX_SEQUENCE_LEN: usize = 64;
+
+/// Foreground colour set by the remote on stdout, restored after tinted stderr.
+///
+/// Only SGR sequences are followed, a sequence split across chunks is kept until it ends.
+#[derive(Debug, Default)]
+struct Foreground {
+    /// Parameters of the last SGR that set the foreground, `None` for the default colour.
+    current: Option<String>,
+    /// Parameters of the control sequence being read, `None` outside of one.
+    sequence: Option<Vec<u8>>,
+    escape: bool,
+}
+
+impl Foreground {
+    fn feed(&mut self, data: &[u8]) {
+        for &byte in data {
+            if self.escape {
+                self.escape = false;
+                if byte == b'[' {
+                    self.sequence = Some(Vec::new());
+                    continue;
+                }
+            }
+
+            match self.sequence.take() {
+                Some(mut params) => match byte {
+                    0x20..=0x3f if params.len() < MAX_SEQUENCE_LEN => {
+                        params.push(byte);
+                        self.sequence = Some(params);
+                    }
+                    b'm' => self.apply(&params),
+                    0x1b => self.escape = true,
+                    _ => {}
+                },
+                None => self.escape = byte == 0x1b,
+            }
+        }
+    }
+
+    fn apply(&mut self, params: &[u8]) {
+        let params = String::from_utf8_lossy(params);
+        let mut params = params.split(';');
+        while let Some(param) = params.next() {
+            match param {
+                "" | "0" | "39" => self.current = None,
+                "30" | "31" | "32" | "33" | "34" | "35" | "36" | "37" | "90" | "91" | "92"
+                | "93" | "94" | "95" | "96" | "97" => self.current = Some(param.to_owned()),
+                "38" | "48" => {
+                    let color: Vec<&str> = match params.next() {
+                        Some("5") => std::iter::once("5").chain(params.next()).collect(),
+                        Some("2") => std::iter::once("2")
+                            .chain(params.by_ref().take(3))
+                            .collect(),
+                        _ => continue,
+                    };
+                    if param == "38" {
+                        self.current = Some(format!("38;{}", color.join(";")));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Sequence setting the foreground back to the one the remote chose.
+    fn restore(&self) -> String {
+        format!("\x1b[{}m", self.current.as_deref().unwrap_or("39"))
+    }
+}
+
+fn stderr_output(data: Vec<u8>, options: &SessionOptions, foreground: &Foreground) -> Vec<u8> {
+    match options.stderr_color {
+        Some((r, g, b)) => {
+            let mut tinted = format!("\x1b[38;2;{};{};{}m", r, g, b).into_bytes();
+            tinted.extend_from_slice(&data);
+            tinted.extend_from_slice(foreground.restore().as_bytes());
+            tinted
+        }
+        None => data,
+    }
+}
+
+/// Write the output of the channel to the pty, until every sender is dropped.
+///
+/// It runs on its own task, so a terminal that doesn't drain the pty doesn't keep the session
+/// from handling resizes or closing.
+async fn write_pty(
+    mut pty: WriteHalf<AsyncPty>,
+    mut output: mpsc::Receiver<Vec<u8>>,
+) -> std::io::Result<()> {
+    while let Some(data) = output.recv().await {
+        pty.write_all(&data).await?;
+    }
+    Ok(())
+}
+
+/// Send the start of `input` the server has room for, the rest waits for
+/// [`ChannelOutput::Writable`].
+async fn send_input(channel: &mut Channel, input: &mut Vec<u8>) -> Result<()> {
+    let sent = channel.write(input).await?;
+    input.drain(..sent);
+    Ok(())
+}
+
+/// Run an interactive session on `slave_pty` until it ends, the last event sent is always
+/// [`SessionEvent::Finished`].
+///
+/// The pty is requested with the current value of `size_receiver`, every later value is sent
+/// to the server as a window change. Dropping the sender of `control` closes the session, the
+/// disconnection is given up after a short timeout if the server doesn't answer.
+pub async fn run_terminal(
+    target: ConnectOptions,
+    slave_pty: OwnedFd,
+    control: mpsc::Receiver<Control>,
+    size_receiver: watch::Receiver<TermSize>,
+    events: EventSender,
+    options: SessionOptions,
+) {
+    let result = terminal(target, slave_pty, control, size_receiver, &events, options).await;
+    event::send(&events, SessionEvent::Finished(result));
+}
+
+async fn terminal(
+    target: ConnectOptions,
+    slave_pty: OwnedFd,
+    mut control: mpsc::Receiver<Control>,
+    mut size_receiver: watch::Receiver<TermSize>,
+    events: &EventSender,
+    options: SessionOptions,
+) -> Result<SessionEnd> {
+    let pty = AsyncPty::new(slave_pty)?;
+
+    let establish = async {
+        let session = Session::connect(&target, events.clone()).await?;
+
+        let size = *size_receiver.borrow_and_update();
+        let channel = session.open_shell(&options, size).await?;
+        Ok::<_, Error>((session, channel))
+    };
+
+    // Closing while connecting gives up right away, the server may be the reason it is slow.
+    let (session, mut channel) = tokio::select! {
+        res = establish => res?,
+        _ = control.recv() => {
+            trace!("session closed while connecting");
+            return Ok(SessionEnd::Closed);
+        }
+    };
+    client::report(events, SessionState::Connected);
+
+    let (mut pty_reader, pty_writer) = tokio::io::split(pty);
+    let (pty_sender, pty_receiver) = mpsc::channel(PTY_QUEUE_SIZE);
+    let writer = tokio::spawn(write_pty(pty_writer, pty_receiver));
+    // Output read from the channel, waiting for room in the queue of the writer.
+    let mut pending: Option<Vec<u8>> = None;
+    // Input read from the pty, waiting for room in the window of the server.
+    let mut input: Vec<u8> = Vec::new();
+    let mut foreground = Foreground::default();
+    let mut closing = false;
+
+    let mut end = SessionEnd::Closed;
+
+    let mut keepalive = options.keepalive_interval.map(|period| {
+        let mut interval = tokio::time::interval_at(Instant::now() + period, period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        interval
+    });
+    let mut last_activity = Instant::now();
+    let mut missed_keepalives = 0;
+    // Probes run on their own task, as waiting for an answer must not hold up the session.
+    let (probe_sender, mut probe_results) = mpsc::channel(1);
+    let mut probe: Option<JoinHandle<()>> = None;
+
+    let mut stats = SessionStats::default();
+    let mut last_stats = stats;
+    let mut stats_interval =
+        tokio::time::interval_at(Instant::now() + STATS_INTERVAL, STATS_INTERVAL);
+
+    let mut buf = vec![0u8; PTY_BUFFER_SIZE];
+    let mut size_closed = false;
+
+    loop {
+        tokio::select! {
+            biased;
+            msg = control.recv() => {
+                // The pane is gone if the sender was dropped.
+                match msg.unwrap_or(Control::Close) {
+                    Control::Close => {
+                        trace!("closing session ...");
+                        // A pending probe holds the connection, which the disconnection needs.
+                        if let Some(probe) = probe.take() {
+                            probe.abort();
+                        }
+                        let disconnect = async {
+                            if let Err(e) = channel.close().await {
+                                warn!("failed to close channel : {}", e);
+                            }
+                            if let Err(e) = session.close().await {
+                                warn!("{}", e);
+                            }
+                        };
+
+                        // The connection is dropped anyway, a hung server must not keep the task alive.
+                        if tokio::time::timeout(DISCONNECT_TIMEOUT, disconnect).await.is_err() {
+                            warn!("the server did not acknowledge the disconnection");
+                        }
+                        closing = true;
+                        break;
+                    }
+                }
+            }
+
+            res = size_receiver.changed(), if !size_closed => {
+                if res.is_err() {
+                    // The pane stopped tracking its size, the close message follows.
+                    size_closed = true;
+                    continue;
+                }
+
+                // Only the latest size matters, intermediate ones were skipped by the watch.
+                let size = *size_receiver.borrow_and_update();
+                trace!("window changed to {}x{}", size.columns, size.rows);
+                if let Err(e) = channel.resize(size).await {
+                    warn!("failed to send window change : {}", e);
+                }
+            }
+
+            permit = pty_sender.reserve(), if pending.is_some() => {
+                match (permit, pending.take()) {
+                    (Ok(permit), Some(data)) => permit.send(data),
+                    (Err(_), _) => {
+                        error!("the pty writer stopped");
+                        break;
+                    }
+                    (Ok(_), None) => {}
+                }
+            }
+
+            // The channel isn't read while the pty is full, so output doesn't pile up here.
+            output = channel.next(), if pending.is_none() => {
+                last_activity = Instant::now();
+                missed_keepalives = 0;
+
+                match output {
+                    Some(ChannelOutput::Stdout(data)) => {
+                        stats.bytes_received += data.len() as u64;
+                        foreground.feed(&data);
+                        pending = Some(data);
+                    }
+                    Some(ChannelOutput::Stderr(data)) => {
+                        stats.bytes_received += data.len() as u64;
+                        pending = Some(stderr_output(data, &options, &foreground));
+                    }
+                    Some(ChannelOutput::Exit(exit)) => {
+                        end = exit;
+                        event::send(events, SessionEvent::Exit(end.clone()));
+                    }
+                    Some(ChannelOutput::Eof) => {
+                        trace!("remote sent eof");
+                    }
+                    Some(ChannelOutput::Writable) => {
+                        if let Err(e) = send_input(&mut channel, &mut input).await {
+                            error!("failed to send data : {}", e);
+                            end = SessionEnd::ConnectionLost;
+                            break;
+                        }
+                    }
+                    Some(ChannelOutput::Closed) | None => {
+                        if let Some(probe) = probe.take() {
+                            probe.abort();
+                        }
+                        if end == SessionEnd::Closed && session.is_closed().await {
+                            trace!("transport closed before the channel");
+                            end = SessionEnd::ConnectionLost;
+                        } else {
+                            trace!("remote closed the channel");
+                        }
+                        break;
+                    }
+                }
+            }
+
+            _ = stats_interval.tick() => {
+                if stats != last_stats {
+                    last_stats = stats;
+                    event::send(events, SessionEvent::Stats(stats));
+                }
+            }
+
+            _ = keepalive_tick(&mut keepalive) => {
+                let period = options.keepalive_interval.unwrap_or_default();
+                if last_activity.elapsed() < period || probe.is_some() {
+                    continue;
+                }
+
+                let session = session.clone();
+                let probe_sender = probe_sender.clone();
+                probe = Some(tokio::spawn(async move {
+                    let _ = probe_sender.send(session.probe(period).await).await;
+                }));
+            }
+
+            Some(alive) = probe_results.recv() => {
+                probe = None;
+                if alive {
+                    last_activity = Instant::now();
+                    missed_keepalives = 0;
+                } else {
+                    missed_keepalives += 1;
+                    warn!("server did not answer keepalive ({}/{})", missed_keepalives, options.keepalive_count_max);
+
+                    if missed_keepalives >= options.keepalive_count_max {
+                        error!("connection lost");
+                        end = SessionEnd::ConnectionLost;
+                        break;
+                    }
+                }
+            }
+
+            // The pty isn't read while the server has no room, so typing faster than it reads
+            // stays in the pty, and the channel is still read meanwhile.
+            res = pty_reader.read(&mut buf), if input.is_empty() => {
+                match res? {
+                    0 => break,
+                    n => {
+                        stats.bytes_sent += n as u64;
+                        input.extend_from_slice(&buf[..n]);
+                        if let Err(e) = send_input(&mut channel, &mut input).await {
+                            error!("failed to send data : {}", e);
+                            end = SessionEnd::ConnectionLost;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    trace!("end of ssh loop");
+
+    if let Some(probe) = probe {
+        probe.abort();
+    }
+
+    // The output received before the end still reaches the terminal, unless the user closed it.
+    drop(pty_sender);
+    if closing {
+        writer.abort();
+    } else {
+        match writer.await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => error!("failed to write to the pty : {}", e),
+            Err(e) => error!("the pty writer failed : {}", e),
+        }
+    }
+
+    if stats != last_stats {
+        event::send(events, SessionEvent::Stats(stats));
+    }
+
+    Ok(end)
+}
@@ -0,0 +1,39 @@
+use flatline_session::{ChannelOutput, SessionEnd};
+
+mod common;
+
+use common::{connect_session, ServerEvent, ShellBehaviour, TestServer};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn exec_reports_the_command_exit_status() {
+    let mut server = TestServer::builder()
+        .shell(ShellBehaviour::Exit(7))
+        .start()
+        .await;
+
+    let session = connect_session(&server).await;
+    let mut channel = session.exec("uptime").await.unwrap();
+
+    let mut outputs = Vec::new();
+    while let Some(output) = channel.next().await {
+        let closed = output == ChannelOutput::Closed;
+        outputs.push(output);
+        if closed {
+            break;
+        }
+    }
+
+    assert_eq!(
+        outputs,
+        [
+            ChannelOutput::Exit(SessionEnd::Exited(7)),
+            ChannelOutput::Eof,
+            ChannelOutput::Closed,
+        ]
+    );
+
+    let exec = server.wait_for(|e| matches!(e, ServerEvent::Exec(_))).await;
+    assert_eq!(exec, ServerEvent::Exec("uptime".to_owned()));
+
+    session.close().await.unwrap();
+}
@@ -0,0 +1,819 @@
+//! In-process SSH server and client helpers shared by the integration tests.
+//!
+//! [`TestServer`] listens on localhost with an auth policy, a host key, a shell behaviour and
+//! an optional injected failure, and reports what the client asked for as [`ServerEvent`]s.
+//! It opens the direct-tcpip channels it is asked for, so it can be used as a jump host or to
+//! forward ports. [`TestTerminal`] runs [`run_terminal`] against it on a real pty and answers
+//! the prompts, [`connect_session`] connects a bare [`Session`].
+
+#![allow(dead_code)]
+
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener},
+    os::fd::OwnedFd,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use flatline_session::{
+    pty::open_pty, run_terminal, ConnectOptions, Control, EventReceiver, HostKeyDecision,
+    HostKeyStatus, Result, Session as ClientSession, SessionEnd, SessionEvent, SessionOptions,
+    SessionState, TermSize,
+};
+use russh::{
+    server::{self, Auth, Msg, Session},
+    Channel, ChannelId, ChannelMsg, CryptoVec, Pty,
+};
+use russh_keys::key::{KeyPair, PublicKey};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    sync::{mpsc, watch},
+};
+
+/// Longest wait for anything in a test, so a broken session fails instead of hanging.
+pub const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Size of the chunks the server writes output in.
+const CHUNK_SIZE: usize = 32 * 1024;
+
+pub const USER: &str = "flatline";
+
+/// What the server runs when the client asks for a shell or a command.
+#[derive(Debug, Clone)]
+pub enum ShellBehaviour {
+    /// Exit right away with the given status.
+    Exit(u32),
+    /// Write the given bytes, then exit with status 0.
+    Output(Arc<Vec<u8>>),
+    /// Write each chunk on its stream in order, then exit with status 0.
+    Streams(Arc<Vec<Stream>>),
+    /// Write back everything the client sends, then exit with status 0 once it sent the given
+    /// number of bytes.
+    Echo(usize),
+    /// Do nothing until the client closes the channel.
+    Idle,
+}
+
+/// Chunk of output written by [`ShellBehaviour::Streams`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stream {
+    Stdout(Vec<u8>),
+    Stderr(Vec<u8>),
+}
+
+/// Failure injected in the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// Refuse every pty request.
+    RejectPty,
+    /// Refuse every shell request.
+    RejectShell,
+    /// Stop answering anything once a shell was started, like a server that went away
+    /// without closing the connection.
+    Unresponsive,
+    /// Refuse every session channel but the first, like a server with `MaxSessions 1`.
+    SingleSession,
+}
+
+/// What the client asked the server for, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerEvent {
+    Auth {
+        user: String,
+        accepted: bool,
+    },
+    KeyAuth {
+        user: String,
+        accepted: bool,
+    },
+    PtyRequest {
+        term: String,
+        columns: u32,
+        rows: u32,
+    },
+    Env {
+        name: String,
+        value: String,
+    },
+    WindowChange {
+        columns: u32,
+        rows: u32,
+    },
+    Shell,
+    Exec(String),
+    Data(Vec<u8>),
+    DirectTcpip {
+        host: String,
+        port: u32,
+    },
+}
+
+#[derive(Clone)]
+struct Handler {
+    password: Option<Arc<str>>,
+    authorized_key: Option<PublicKey>,
+    shell: ShellBehaviour,
+    failure: Option<Failure>,
+    events: mpsc::UnboundedSender<ServerEvent>,
+    started: bool,
+    /// Channel and status to exit with once the output was sent.
+    exiting: Option<(ChannelId, u32)>,
+    /// Number of session channels opened.
+    sessions: u32,
+    /// Number of bytes received from the client.
+    received: usize,
+}
+
+impl Handler {
+    fn record(&self, event: ServerEvent) {
+        let _ = self.events.send(event);
+    }
+
+    fn start(&mut self, channel: ChannelId, session: &mut Session) {
+        self.started = true;
+        session.channel_success(channel);
+
+        match self.shell.clone() {
+            ShellBehaviour::Exit(status) => self.exiting = Some((channel, status)),
+            ShellBehaviour::Output(output) => {
+                // What doesn't fit in the client's window is queued by russh.
+                for chunk in output.chunks(CHUNK_SIZE) {
+                    session.data(channel, CryptoVec::from_slice(chunk));
+                }
+                self.exiting = Some((channel, 0));
+            }
+            ShellBehaviour::Streams(streams) => {
+                for stream in streams.iter() {
+                    match stream {
+                        Stream::Stdout(data) => session.data(channel, CryptoVec::from_slice(data)),
+                        Stream::Stderr(data) => {
+                            session.extended_data(channel, 1, CryptoVec::from_slice(data))
+                        }
+                    }
+                }
+                self.exiting = Some((channel, 0));
+            }
+            ShellBehaviour::Echo(_) | ShellBehaviour::Idle => {}
+        }
+        self.exit_when_sent(session);
+    }
+
+    /// Exit once the queued output was sent, as closing the channel discards it.
+    fn exit_when_sent(&mut self, session: &mut Session) {
+        let Some((channel, status)) = self.exiting else {
+            return;
+        };
+        if session.has_pending_data(channel) {
+            return;
+        }
+
+        self.exiting = None;
+        session.exit_status_request(channel, status);
+        session.eof(channel);
+        session.close(channel);
+    }
+
+    async fn hang_if_unresponsive(&self) {
+        if self.started && self.failure == Some(Failure::Unresponsive) {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+impl server::Server for Handler {
+    type Handler = Self;
+
+    fn new_client(&mut self, _peer_addr: Option<SocketAddr>) -> Self {
+        self.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl server::Handler for Handler {
+    type Error = russh::Error;
+
+    async fn auth_password(
+        self,
+        user: &str,
+        password: &str,
+    ) -> std::result::Result<(Self, Auth), Self::Error> {
+        let accepted = self.password.as_deref().map_or(true, |p| p == password);
+        self.record(ServerEvent::Auth {
+            user: user.to_owned(),
+            accepted,
+        });
+
+        let auth = if accepted {
+            Auth::Accept
+        } else {
+            Auth::Reject {
+                proceed_with_methods: None,
+            }
+        };
+        Ok((self, auth))
+    }
+
+    async fn auth_publickey(
+        self,
+        user: &str,
+        public_key: &PublicKey,
+    ) -> std::result::Result<(Self, Auth), Self::Error> {
+        let accepted = self.authorized_key.as_ref() == Some(public_key);
+        self.record(ServerEvent::KeyAuth {
+            user: user.to_owned(),
+            accepted,
+        });
+
+        let auth = if accepted {
+            Auth::Accept
+        } else {
+            Auth::Reject {
+                proceed_with_methods: None,
+            }
+        };
+        Ok((self, auth))
+    }
+
+    async fn channel_open_session(
+        mut self,
+        _channel: Channel<Msg>,
+        session: Session,
+    ) -> std::result::Result<(Self, bool, Session), Self::Error> {
+        self.hang_if_unresponsive().await;
+        self.sessions += 1;
+        let accepted = self.failure != Some(Failure::SingleSession) || self.sessions == 1;
+        Ok((self, accepted, session))
+    }
+
+    async fn channel_open_direct_tcpip(
+        self,
+        channel: Channel<Msg>,
+        host_to_connect: &str,
+        port_to_connect: u32,
+        _originator_address: &str,
+        _originator_port: u32,
+        session: Session,
+    ) -> std::result::Result<(Self, bool, Session), Self::Error> {
+        self.record(ServerEvent::DirectTcpip {
+            host: host_to_connect.to_owned(),
+            port: port_to_connect,
+        });
+
+        let Ok(stream) = TcpStream::connect((host_to_connect, port_to_connect as u16)).await else {
+            return Ok((self, false, session));
+        };
+        tokio::spawn(forward(stream, channel, session.handle()));
+        Ok((self, true, session))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn pty_request(
+        self,
+        channel: ChannelId,
+        term: &str,
+        col_width: u32,
+        row_height: u32,
+        _pix_width: u32,
+        _pix_height: u32,
+        _modes: &[(Pty, u32)],
+        mut session: Session,
+    ) -> std::result::Result<(Self, Session), Self::Error> {
+        self.record(ServerEvent::PtyRequest {
+            term: term.to_owned(),
+            columns: col_width,
+            rows: row_height,
+        });
+
+        if self.failure == Some(Failure::RejectPty) {
+            session.channel_failure(channel);
+        } else {
+            session.channel_success(channel);
+        }
+        Ok((self, session))
+    }
+
+    async fn env_request(
+        self,
+        channel: ChannelId,
+        variable_name: &str,
+        variable_value: &str,
+        mut session: Session,
+    ) -> std::result::Result<(Self, Session), Self::Error> {
+        self.record(ServerEvent::Env {
+            name: variable_name.to_owned(),
+            value: variable_value.to_owned(),
+        });
+        session.channel_success(channel);
+        Ok((self, session))
+    }
+
+    async fn window_change_request(
+        self,
+        _channel: ChannelId,
+        col_width: u32,
+        row_height: u32,
+        _pix_width: u32,
+        _pix_height: u32,
+        session: Session,
+    ) -> std::result::Result<(Self, Session), Self::Error> {
+        self.hang_if_unresponsive().await;
+        self.record(ServerEvent::WindowChange {
+            columns: col_width,
+            rows: row_height,
+        });
+        Ok((self, session))
+    }
+
+    async fn shell_request(
+        mut self,
+        channel: ChannelId,
+        mut session: Session,
+    ) -> std::result::Result<(Self, Session), Self::Error> {
+        self.record(ServerEvent::Shell);
+        if self.failure == Some(Failure::RejectShell) {
+            session.channel_failure(channel);
+        } else {
+            self.start(channel, &mut session);
+        }
+        Ok((self, session))
+    }
+
+    async fn exec_request(
+        mut self,
+        channel: ChannelId,
+        data: &[u8],
+        mut session: Session,
+    ) -> std::result::Result<(Self, Session), Self::Error> {
+        self.record(ServerEvent::Exec(
+            String::from_utf8_lossy(data).into_owned(),
+        ));
+        self.start(channel, &mut session);
+        Ok((self, session))
+    }
+
+    async fn window_adjusted(
+        mut self,
+        _channel: ChannelId,
+        _new_size: u32,
+        mut session: Session,
+    ) -> std::result::Result<(Self, Session), Self::Error> {
+        self.exit_when_sent(&mut session);
+        Ok((self, session))
+    }
+
+    async fn data(
+        mut self,
+        channel: ChannelId,
+        data: &[u8],
+        mut session: Session,
+    ) -> std::result::Result<(Self, Session), Self::Error> {
+        self.hang_if_unresponsive().await;
+        self.record(ServerEvent::Data(data.to_vec()));
+
+        if let ShellBehaviour::Echo(len) = self.shell {
+            session.data(channel, CryptoVec::from_slice(data));
+            self.received += data.len();
+            if self.received >= len {
+                self.exiting = Some((channel, 0));
+                self.exit_when_sent(&mut session);
+            }
+        }
+        Ok((self, session))
+    }
+}
+
+/// Copy data both ways between a direct-tcpip channel and the connection it asked for.
+///
+/// Data goes through `handle`, which queues what doesn't fit in the window of the client
+/// instead of waiting for it, so the channel is read all along.
+async fn forward(stream: TcpStream, mut channel: Channel<Msg>, handle: server::Handle) {
+    let id = channel.id();
+    let (mut reader, mut writer) = stream.into_split();
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    let mut output: VecDeque<u8> = VecDeque::new();
+    let (mut local_eof, mut remote_eof, mut shut_down) = (false, false, false);
+
+    loop {
+        if remote_eof && output.is_empty() && !shut_down {
+            shut_down = true;
+            let _ = writer.shutdown().await;
+        }
+        if shut_down && local_eof {
+            break;
+        }
+
+        tokio::select! {
+            res = reader.read(&mut buf), if !local_eof => match res {
+                Ok(0) | Err(_) => {
+                    local_eof = true;
+                    let _ = handle.eof(id).await;
+                }
+                Ok(n) => {
+                    if handle.data(id, CryptoVec::from_slice(&buf[..n])).await.is_err() {
+                        break;
+                    }
+                }
+            },
+            res = writer.write(output.as_slices().0), if !output.is_empty() => match res {
+                Ok(n) => {
+                    output.drain(..n);
+                }
+                Err(_) => break,
+            },
+            msg = channel.wait(), if !remote_eof => match msg {
+                Some(ChannelMsg::Data { data }) => output.extend(data.iter()),
+                Some(ChannelMsg::Eof) => remote_eof = true,
+                Some(ChannelMsg::Close) | None => break,
+                _ => {}
+            },
+        }
+    }
+
+    let _ = handle.close(id).await;
+}
+
+/// Builder of a [`TestServer`], which accepts any password and exits right away by default.
+pub struct TestServerBuilder {
+    password: Option<String>,
+    authorized_key: Option<PublicKey>,
+    host_key: Option<KeyPair>,
+    shell: ShellBehaviour,
+    failure: Option<Failure>,
+    window_size: Option<u32>,
+}
+
+impl TestServerBuilder {
+    /// Only accept `password`.
+    pub fn password(self, password: &str) -> Self {
+        Self {
+            password: Some(password.to_owned()),
+            ..self
+        }
+    }
+
+    /// Accept `key` for public key authentication, no key is accepted otherwise.
+    pub fn authorized_key(self, key: PublicKey) -> Self {
+        Self {
+            authorized_key: Some(key),
+            ..self
+        }
+    }
+
+    pub fn host_key(self, host_key: KeyPair) -> Self {
+        Self {
+            host_key: Some(host_key),
+            ..self
+        }
+    }
+
+    pub fn shell(self, shell: ShellBehaviour) -> Self {
+        Self { shell, ..self }
+    }
+
+    pub fn failure(self, failure: Failure) -> Self {
+        Self {
+            failure: Some(failure),
+            ..self
+        }
+    }
+
+    /// Let clients send only `window_size` bytes on a channel before waiting for the server.
+    pub fn window_size(self, window_size: u32) -> Self {
+        Self {
+            window_size: Some(window_size),
+            ..self
+        }
+    }
+
+    pub async fn start(self) -> TestServer {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let host_key = self
+            .host_key
+            .unwrap_or_else(|| KeyPair::generate_ed25519().unwrap());
+        let public_key = host_key.clone_public_key().unwrap();
+
+        let defaults = server::Config::default();
+        let config = Arc::new(server::Config {
+            keys: vec![host_key],
+            auth_rejection_time: Duration::from_millis(10),
+            window_size: self.window_size.unwrap_or(defaults.window_size),
+            maximum_packet_size: self
+                .window_size
+                .map_or(defaults.maximum_packet_size, |size| {
+                    size.min(defaults.maximum_packet_size)
+                }),
+            ..defaults
+        });
+
+        let (events_sender, events) = mpsc::unbounded_channel();
+        let handler = Handler {
+            password: self.password.map(Arc::from),
+            authorized_key: self.authorized_key,
+            shell: self.shell,
+            failure: self.failure,
+            events: events_sender,
+            started: false,
+            exiting: None,
+            sessions: 0,
+            received: 0,
+        };
+
+        tokio::spawn(server::run(config, ("127.0.0.1", port), handler));
+
+        while tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        TestServer {
+            port,
+            public_key,
+            known_hosts: temp_path("known_hosts"),
+            events,
+        }
+    }
+}
+
+/// Unique path in the temporary directory, the file itself is not created.
+pub fn temp_path(name: &str) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let dir = std::env::temp_dir().join(format!(
+        "flatline-session-tests-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+/// SSH server running on localhost for the duration of a test.
+pub struct TestServer {
+    pub port: u16,
+    pub public_key: PublicKey,
+    /// Known hosts file the clients of this server use, empty until a key is trusted.
+    pub known_hosts: PathBuf,
+    events: mpsc::UnboundedReceiver<ServerEvent>,
+}
+
+impl TestServer {
+    pub fn builder() -> TestServerBuilder {
+        TestServerBuilder {
+            password: None,
+            authorized_key: None,
+            host_key: None,
+            shell: ShellBehaviour::Exit(0),
+            failure: None,
+            window_size: None,
+        }
+    }
+
+    pub fn connect_options(&self) -> ConnectOptions {
+        ConnectOptions {
+            user: Some(USER.to_owned()),
+            known_hosts: Some(self.known_hosts.clone()),
+            ..ConnectOptions::new("127.0.0.1", self.port)
+        }
+    }
+
+    /// Add `key` to the known hosts of the clients as the key of this server.
+    pub fn trust_key(&self, key: &PublicKey) {
+        russh_keys::learn_known_hosts_path("127.0.0.1", self.port, key, &self.known_hosts).unwrap();
+    }
+
+    /// Wait for the next event matching `predicate`, skipping the others.
+    pub async fn wait_for(&mut self, predicate: impl Fn(&ServerEvent) -> bool) -> ServerEvent {
+        tokio::time::timeout(TIMEOUT, async {
+            loop {
+                let event = self.events.recv().await.expect("the server stopped");
+                if predicate(&event) {
+                    return event;
+                }
+            }
+        })
+        .await
+        .expect("the server never received the expected event")
+    }
+
+    /// Events received so far.
+    pub fn received(&mut self) -> Vec<ServerEvent> {
+        std::iter::from_fn(|| self.events.try_recv().ok()).collect()
+    }
+}
+
+/// How a [`TestTerminal`] answers the session's requests.
+#[derive(Debug, Clone)]
+pub struct Answers {
+    pub host_key: HostKeyDecision,
+    /// Password given at every prompt, `None` cancels the prompt.
+    pub password: Option<String>,
+}
+
+impl Default for Answers {
+    fn default() -> Self {
+        Self {
+            host_key: HostKeyDecision::AcceptOnce,
+            password: Some("password".to_owned()),
+        }
+    }
+}
+
+/// Options without any environment or keepalive, so tests only see what they configure.
+pub fn quiet_options() -> SessionOptions {
+    SessionOptions {
+        env: Vec::new(),
+        send_env: Vec::new(),
+        keepalive_interval: None,
+        ..Default::default()
+    }
+}
+
+/// Interactive session on a real pty, whose output is collected on another thread.
+pub struct TestTerminal {
+    pub control: mpsc::Sender<Control>,
+    pub size: watch::Sender<TermSize>,
+    events: EventReceiver,
+    answers: Answers,
+    /// Host key statuses the session asked about.
+    pub host_key_prompts: Vec<HostKeyStatus>,
+    /// Number of passwords the session asked for.
+    pub password_prompts: u32,
+    output: std::sync::mpsc::Receiver<Vec<u8>>,
+    master: File,
+    // Keep the slave open until everything was read, closing it could discard buffered data.
+    _slave: OwnedFd,
+}
+
+impl TestTerminal {
+    pub fn start(
+        connect: ConnectOptions,
+        options: SessionOptions,
+        size: TermSize,
+        answers: Answers,
+    ) -> Self {
+        let (master_pty, slave_pty) = open_pty().unwrap();
+        let slave_guard = slave_pty.try_clone().unwrap();
+        let master = File::from(master_pty.try_clone().unwrap());
+
+        let (output_sender, output) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let mut master = File::from(master_pty);
+            let mut buf = vec![0u8; 64 * 1024];
+
+            loop {
+                match master.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        if output_sender.send(buf[..n].to_vec()).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+
+        let (control, control_receiver) = mpsc::channel(10);
+        let (size_sender, size_receiver) = watch::channel(size);
+        let (events_sender, events) = mpsc::unbounded_channel();
+        tokio::spawn(run_terminal(
+            connect,
+            slave_pty,
+            control_receiver,
+            size_receiver,
+            events_sender,
+            options,
+        ));
+
+        Self {
+            control,
+            size: size_sender,
+            events,
+            answers,
+            host_key_prompts: Vec::new(),
+            password_prompts: 0,
+            output,
+            master,
+            _slave: slave_guard,
+        }
+    }
+
+    /// Answer the session's requests until an event matches `predicate`.
+    pub async fn wait_for(&mut self, predicate: impl Fn(&SessionEvent) -> bool) -> SessionEvent {
+        tokio::time::timeout(TIMEOUT, async {
+            loop {
+                let event = self
+                    .events
+                    .recv()
+                    .await
+                    .expect("the session stopped without finishing");
+
+                if predicate(&event) {
+                    return event;
+                }
+
+                match event {
+                    SessionEvent::HostKey { status, reply, .. } => {
+                        self.host_key_prompts.push(status);
+                        reply.send(self.answers.host_key);
+                    }
+                    SessionEvent::Prompt { reply, .. } => {
+                        self.password_prompts += 1;
+                        reply.send(self.answers.password.clone());
+                    }
+                    SessionEvent::Finished(result) => {
+                        panic!("the session finished early : {:?}", result)
+                    }
+                    _ => {}
+                }
+            }
+        })
+        .await
+        .expect("the session never sent the expected event")
+    }
+
+    pub async fn wait_connected(&mut self) {
+        self.wait_for(|e| matches!(e, SessionEvent::StateChanged(SessionState::Connected)))
+            .await;
+    }
+
+    /// Answer the session's requests until it finishes.
+    pub async fn finish(&mut self) -> Result<SessionEnd> {
+        match self
+            .wait_for(|e| matches!(e, SessionEvent::Finished(_)))
+            .await
+        {
+            SessionEvent::Finished(result) => result,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Type `input` in the terminal, from another thread as the pty only takes a little at once.
+    pub fn type_input(&self, input: Vec<u8>) {
+        let mut master = self.master.try_clone().unwrap();
+        std::thread::spawn(move || master.write_all(&input));
+    }
+
+    /// Wait until `len` bytes were written to the pty and return them.
+    pub fn output(&self, len: usize) -> Vec<u8> {
+        let mut received = Vec::with_capacity(len);
+        while received.len() < len {
+            match self.output.recv_timeout(TIMEOUT) {
+                Ok(data) => received.extend_from_slice(&data),
+                Err(_) => break,
+            }
+        }
+        received
+    }
+}
+
+/// Connect a [`ClientSession`] to `server`, accepting its host key and giving the default
+/// password.
+pub async fn connect_session(server: &TestServer) -> ClientSession {
+    let (events_sender, mut events) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(event) = events.recv().await {
+            match event {
+                SessionEvent::HostKey { reply, .. } => reply.send(HostKeyDecision::AcceptOnce),
+                SessionEvent::Prompt { reply, .. } => reply.send(Answers::default().password),
+                _ => {}
+            }
+        }
+    });
+
+    tokio::time::timeout(
+        TIMEOUT,
+        ClientSession::connect(&server.connect_options(), events_sender),
+    )
+    .await
+    .expect("connecting took too long")
+    .unwrap()
+}
+
+/// Start a terminal of 80x24 cells on `server` with the default answers.
+pub fn start_terminal(server: &TestServer, options: SessionOptions) -> TestTerminal {
+    TestTerminal::start(
+        server.connect_options(),
+        options,
+        TermSize {
+            columns: 80,
+            rows: 24,
+            ..Default::default()
+        },
+        Answers::default(),
+    )
+}
@@ -0,0 +1,186 @@
+use flatline_session::{ConnectOptions, SessionEnd};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+mod common;
+
+use common::{
+    connect_session, quiet_options, Answers, ServerEvent, ShellBehaviour, TestServer, TestTerminal,
+    TIMEOUT,
+};
+
+const PAYLOAD_SIZE: usize = 1024 * 1024;
+
+/// Window small enough for the payloads to fill it many times.
+const SMALL_WINDOW: u32 = 16 * 1024;
+
+fn payload() -> Vec<u8> {
+    (0..PAYLOAD_SIZE).map(|i| (i % 251) as u8).collect()
+}
+
+/// Listen on localhost and write back everything received on each connection.
+async fn echo_server() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let (mut reader, mut writer) = stream.split();
+                let _ = tokio::io::copy(&mut reader, &mut writer).await;
+            });
+        }
+    });
+
+    port
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn local_forward_relays_both_ways() {
+    let mut server = TestServer::builder()
+        .shell(ShellBehaviour::Idle)
+        .window_size(SMALL_WINDOW)
+        .start()
+        .await;
+    let echo_port = echo_server().await;
+
+    let session = connect_session(&server).await;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let forward = session
+        .forward_local(listener, "127.0.0.1", echo_port)
+        .unwrap();
+
+    let stream = TcpStream::connect(forward.local_addr()).await.unwrap();
+    let (mut reader, mut writer) = stream.into_split();
+    let payload = payload();
+    let sent = payload.clone();
+    tokio::spawn(async move {
+        writer.write_all(&sent).await.unwrap();
+        writer.shutdown().await.unwrap();
+    });
+
+    // The echo arrives while the relay waits for the window of the server.
+    let mut received = Vec::new();
+    tokio::time::timeout(TIMEOUT, reader.read_to_end(&mut received))
+        .await
+        .expect("the forwarded connection was not closed")
+        .unwrap();
+    assert_eq!(received.len(), payload.len());
+    assert!(received == payload, "the forwarded data was corrupted");
+
+    let request = server
+        .wait_for(|e| matches!(e, ServerEvent::DirectTcpip { .. }))
+        .await;
+    assert_eq!(
+        request,
+        ServerEvent::DirectTcpip {
+            host: "127.0.0.1".to_owned(),
+            port: echo_port as u32,
+        }
+    );
+
+    session.close().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn jump_hosts_carry_the_session() {
+    let mut first = TestServer::builder()
+        .window_size(SMALL_WINDOW)
+        .start()
+        .await;
+    let mut second = TestServer::builder()
+        .window_size(SMALL_WINDOW)
+        .start()
+        .await;
+    let mut destination = TestServer::builder()
+        .shell(ShellBehaviour::Echo(PAYLOAD_SIZE))
+        .start()
+        .await;
+
+    let options = ConnectOptions {
+        jump_hosts: vec![first.connect_options(), second.connect_options()],
+        ..destination.connect_options()
+    };
+    let mut terminal = TestTerminal::start(
+        options,
+        quiet_options(),
+        Default::default(),
+        Answers::default(),
+    );
+    terminal.wait_connected().await;
+
+    let payload = payload();
+    terminal.type_input(payload.clone());
+
+    assert_eq!(terminal.finish().await.unwrap(), SessionEnd::Exited(0));
+    let received = terminal.output(PAYLOAD_SIZE);
+    assert_eq!(received.len(), payload.len());
+    assert!(
+        received == payload,
+        "the session through the jump hosts was corrupted"
+    );
+
+    // Each host was asked for the next one, and only the destination ran the shell.
+    let request = first
+        .wait_for(|e| matches!(e, ServerEvent::DirectTcpip { .. }))
+        .await;
+    assert_eq!(
+        request,
+        ServerEvent::DirectTcpip {
+            host: "127.0.0.1".to_owned(),
+            port: second.port as u32,
+        }
+    );
+    let request = second
+        .wait_for(|e| matches!(e, ServerEvent::DirectTcpip { .. }))
+        .await;
+    assert_eq!(
+        request,
+        ServerEvent::DirectTcpip {
+            host: "127.0.0.1".to_owned(),
+            port: destination.port as u32,
+        }
+    );
+    destination
+        .wait_for(|e| matches!(e, ServerEvent::Shell))
+        .await;
+    assert_eq!(terminal.host_key_prompts.len(), 3);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn proxy_command_carries_the_session() {
+    let mut server = TestServer::builder()
+        .shell(ShellBehaviour::Echo(PAYLOAD_SIZE))
+        .window_size(SMALL_WINDOW)
+        .start()
+        .await;
+
+    // bash opens the TCP connection itself, so the session only goes through the pipes.
+    let options = ConnectOptions {
+        proxy_command: Some(
+            "exec bash -c 'exec 2>/dev/null 3<>/dev/tcp/%h/%p; cat <&3 & exec cat >&3'".to_owned(),
+        ),
+        ..server.connect_options()
+    };
+    let mut terminal = TestTerminal::start(
+        options,
+        quiet_options(),
+        Default::default(),
+        Answers::default(),
+    );
+    terminal.wait_connected().await;
+
+    let payload = payload();
+    terminal.type_input(payload.clone());
+
+    assert_eq!(terminal.finish().await.unwrap(), SessionEnd::Exited(0));
+    let received = terminal.output(PAYLOAD_SIZE);
+    assert_eq!(received.len(), payload.len());
+    assert!(
+        received == payload,
+        "the session through the proxy command was corrupted"
+    );
+    server.wait_for(|e| matches!(e, ServerEvent::Shell)).await;
+}
@@ -0,0 +1,77 @@
+use flatline_session::{Error, HostKeyDecision, HostKeyStatus, SessionEnd};
+use russh_keys::key::KeyPair;
+
+mod common;
+
+use common::{quiet_options, Answers, TestServer, TestTerminal};
+
+fn start(server: &TestServer, host_key: HostKeyDecision) -> TestTerminal {
+    TestTerminal::start(
+        server.connect_options(),
+        quiet_options(),
+        Default::default(),
+        Answers {
+            host_key,
+            ..Default::default()
+        },
+    )
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn trusted_key_is_not_prompted() {
+    let server = TestServer::builder().start().await;
+    server.trust_key(&server.public_key);
+
+    let mut terminal = start(&server, HostKeyDecision::Reject);
+
+    assert_eq!(terminal.finish().await.unwrap(), SessionEnd::Exited(0));
+    assert!(terminal.host_key_prompts.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn saved_key_is_trusted_next_time() {
+    let server = TestServer::builder().start().await;
+
+    let mut terminal = start(&server, HostKeyDecision::AcceptAndSave);
+    assert_eq!(terminal.finish().await.unwrap(), SessionEnd::Exited(0));
+    assert_eq!(terminal.host_key_prompts, [HostKeyStatus::Unknown]);
+
+    let mut terminal = start(&server, HostKeyDecision::Reject);
+    assert_eq!(terminal.finish().await.unwrap(), SessionEnd::Exited(0));
+    assert!(terminal.host_key_prompts.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejected_key_fails_the_session() {
+    let server = TestServer::builder().start().await;
+
+    let mut terminal = start(&server, HostKeyDecision::Reject);
+
+    assert!(matches!(
+        terminal.finish().await,
+        Err(Error::HostKey { .. })
+    ));
+    assert_eq!(terminal.host_key_prompts, [HostKeyStatus::Unknown]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mismatching_key_is_reported_as_changed() {
+    let server = TestServer::builder()
+        .host_key(KeyPair::generate_ed25519().unwrap())
+        .start()
+        .await;
+
+    let other_key = KeyPair::generate_ed25519().unwrap();
+    server.trust_key(&other_key.clone_public_key().unwrap());
+
+    let mut terminal = start(&server, HostKeyDecision::Reject);
+
+    assert!(matches!(
+        terminal.finish().await,
+        Err(Error::HostKey { .. })
+    ));
+    assert!(matches!(
+        terminal.host_key_prompts[..],
+        [HostKeyStatus::Changed { .. }]
+    ));
+}
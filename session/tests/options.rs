@@ -0,0 +1,84 @@
+use flatline_session::{percent_decode, ConnectOptions, DEFAULT_PORT};
+
+fn parse(s: &str) -> ConnectOptions {
+    s.parse().unwrap()
+}
+
+#[test]
+fn destination_with_user_and_port() {
+    let options = parse("alice@db1:2222");
+    assert_eq!(options.user.as_deref(), Some("alice"));
+    assert_eq!(options.host, "db1");
+    assert_eq!(options.port, 2222);
+
+    let options = parse("db1");
+    assert_eq!(options.user, None);
+    assert_eq!(options.port, DEFAULT_PORT);
+}
+
+#[test]
+fn ssh_uri() {
+    let options = parse("ssh://alice@db1:2222");
+    assert_eq!(options.user.as_deref(), Some("alice"));
+    assert_eq!(options.host, "db1");
+    assert_eq!(options.port, 2222);
+
+    let options = parse("SSH://db1/");
+    assert_eq!(options.user, None);
+    assert_eq!(options.host, "db1");
+    assert_eq!(options.port, DEFAULT_PORT);
+}
+
+#[test]
+fn ssh_uri_user_info_is_decoded() {
+    let options = parse("ssh://first%20last;fingerprint=ssh-ed25519-abc@[::1]:2200");
+    assert_eq!(options.user.as_deref(), Some("first last"));
+    assert_eq!(options.host, "::1");
+    assert_eq!(options.port, 2200);
+}
+
+#[test]
+fn invalid_destinations_are_rejected() {
+    for target in [
+        "",
+        "@db1",
+        "db1:0",
+        "db1:ssh",
+        "ssh://",
+        "ssh://db1/path",
+        "ssh://%zz@db1",
+        "[::1",
+    ] {
+        assert!(
+            target.parse::<ConnectOptions>().is_err(),
+            "{:?} should be invalid",
+            target
+        );
+    }
+}
+
+#[test]
+fn display_round_trips() {
+    for target in ["alice@db1:2222", "db1", "[::1]:2200"] {
+        assert_eq!(parse(target).to_string(), target);
+    }
+}
+
+#[test]
+fn percent_escapes_are_decoded() {
+    assert_eq!(percent_decode("web%2001").as_deref(), Some("web 01"));
+    assert_eq!(percent_decode("caf%C3%A9").as_deref(), Some("café"));
+    assert_eq!(percent_decode("plain").as_deref(), Some("plain"));
+    assert_eq!(percent_decode("truncated%2"), None);
+    assert_eq!(percent_decode("invalid%zz"), None);
+    assert_eq!(percent_decode("latin1%E9"), None);
+}
+
+#[test]
+fn proxy_command_tokens() {
+    let options = parse("alice@db1:2222");
+    assert_eq!(
+        options.expand_proxy_command("nc -X 5 -x proxy:1080 %h %p # %r %% %x %"),
+        "nc -X 5 -x proxy:1080 db1 2222 # alice % %x %"
+    );
+}
@@ -0,0 +1,274 @@
+use std::{sync::Arc, time::Duration};
+
+use flatline_session::{Control, Error, SessionEnd, SessionOptions, TermSize};
+
+mod common;
+
+use common::{
+    quiet_options, start_terminal, Failure, ServerEvent, ShellBehaviour, Stream, TestServer,
+};
+
+const PAYLOAD_SIZE: usize = 8 * 1024 * 1024;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn large_output_reaches_the_pty_intact() {
+    let payload: Vec<u8> = (0..PAYLOAD_SIZE).map(|i| (i % 251) as u8).collect();
+    let server = TestServer::builder()
+        .shell(ShellBehaviour::Output(Arc::new(payload.clone())))
+        .start()
+        .await;
+
+    let mut terminal = start_terminal(&server, quiet_options());
+
+    assert_eq!(terminal.finish().await.unwrap(), SessionEnd::Exited(0));
+
+    let received = terminal.output(PAYLOAD_SIZE);
+    assert_eq!(received.len(), payload.len());
+    assert!(received == payload, "the payload was corrupted");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn large_input_does_not_lose_output() {
+    const INPUT_SIZE: usize = 1024 * 1024;
+
+    let input: Vec<u8> = (0..INPUT_SIZE).map(|i| (i % 251) as u8).collect();
+    let server = TestServer::builder()
+        .shell(ShellBehaviour::Echo(INPUT_SIZE))
+        .window_size(16 * 1024)
+        .start()
+        .await;
+
+    let mut terminal = start_terminal(&server, quiet_options());
+    terminal.wait_connected().await;
+    terminal.type_input(input.clone());
+
+    // The echo and the exit status arrive while the client waits for the window of the server.
+    assert_eq!(terminal.finish().await.unwrap(), SessionEnd::Exited(0));
+
+    let received = terminal.output(INPUT_SIZE);
+    assert_eq!(received.len(), input.len());
+    assert!(received == input, "the echo was corrupted");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tinted_stderr_restores_the_remote_foreground() {
+    let server = TestServer::builder()
+        .shell(ShellBehaviour::Streams(Arc::new(vec![
+            Stream::Stdout(b"\x1b[1;3".to_vec()),
+            Stream::Stdout(b"8;5;208mout".to_vec()),
+            Stream::Stderr(b"err".to_vec()),
+            Stream::Stdout(b"\x1b[0mout".to_vec()),
+            Stream::Stderr(b"err".to_vec()),
+        ])))
+        .start()
+        .await;
+
+    let options = SessionOptions {
+        stderr_color: Some((1, 2, 3)),
+        ..quiet_options()
+    };
+    let mut terminal = start_terminal(&server, options);
+
+    assert_eq!(terminal.finish().await.unwrap(), SessionEnd::Exited(0));
+
+    let expected: &[u8] = b"\x1b[1;38;5;208mout\x1b[38;2;1;2;3merr\x1b[38;5;208m\
+        \x1b[0mout\x1b[38;2;1;2;3merr\x1b[39m";
+    assert_eq!(
+        String::from_utf8_lossy(&terminal.output(expected.len())),
+        String::from_utf8_lossy(expected)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn exit_status_is_reported() {
+    let server = TestServer::builder()
+        .shell(ShellBehaviour::Exit(42))
+        .start()
+        .await;
+
+    let mut terminal = start_terminal(&server, quiet_options());
+
+    assert_eq!(terminal.finish().await.unwrap(), SessionEnd::Exited(42));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn resizes_reach_the_server() {
+    let mut server = TestServer::builder()
+        .shell(ShellBehaviour::Idle)
+        .start()
+        .await;
+
+    let mut terminal = start_terminal(&server, quiet_options());
+    terminal.wait_connected().await;
+
+    let pty = server
+        .wait_for(|e| matches!(e, ServerEvent::PtyRequest { .. }))
+        .await;
+    assert_eq!(
+        pty,
+        ServerEvent::PtyRequest {
+            term: "xterm-256color".to_owned(),
+            columns: 80,
+            rows: 24,
+        }
+    );
+
+    terminal.size.send_replace(TermSize {
+        columns: 132,
+        rows: 43,
+        ..Default::default()
+    });
+
+    let change = server
+        .wait_for(|e| matches!(e, ServerEvent::WindowChange { .. }))
+        .await;
+    assert_eq!(
+        change,
+        ServerEvent::WindowChange {
+            columns: 132,
+            rows: 43,
+        }
+    );
+
+    terminal.control.send(Control::Close).await.unwrap();
+    assert_eq!(terminal.finish().await.unwrap(), SessionEnd::Closed);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn environment_is_sent_before_the_shell() {
+    let mut server = TestServer::builder().start().await;
+
+    let options = SessionOptions {
+        env: vec![("FLATLINE_TEST".to_owned(), "1".to_owned())],
+        ..quiet_options()
+    };
+    let mut terminal = start_terminal(&server, options);
+    terminal.finish().await.unwrap();
+
+    let events: Vec<_> = server
+        .received()
+        .into_iter()
+        .filter(|e| matches!(e, ServerEvent::Env { .. } | ServerEvent::Shell))
+        .collect();
+    assert_eq!(
+        events,
+        [
+            ServerEvent::Env {
+                name: "FLATLINE_TEST".to_owned(),
+                value: "1".to_owned(),
+            },
+            ServerEvent::Shell,
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejected_pty_fails_the_session() {
+    let server = TestServer::builder()
+        .failure(Failure::RejectPty)
+        .start()
+        .await;
+
+    let mut terminal = start_terminal(&server, quiet_options());
+
+    assert!(matches!(terminal.finish().await, Err(Error::Pty { .. })));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejected_shell_fails_the_session() {
+    let server = TestServer::builder()
+        .failure(Failure::RejectShell)
+        .start()
+        .await;
+
+    let mut terminal = start_terminal(&server, quiet_options());
+
+    assert!(matches!(terminal.finish().await, Err(Error::Shell)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unresponsive_server_is_detected_by_keepalives() {
+    let server = TestServer::builder()
+        .shell(ShellBehaviour::Idle)
+        .failure(Failure::Unresponsive)
+        .start()
+        .await;
+
+    let options = SessionOptions {
+        keepalive_interval: Some(Duration::from_millis(200)),
+        keepalive_count_max: 2,
+        ..quiet_options()
+    };
+    let mut terminal = start_terminal(&server, options);
+
+    assert_eq!(terminal.finish().await.unwrap(), SessionEnd::ConnectionLost);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn refused_keepalive_channels_count_as_answers() {
+    let server = TestServer::builder()
+        .shell(ShellBehaviour::Idle)
+        .failure(Failure::SingleSession)
+        .start()
+        .await;
+
+    let options = SessionOptions {
+        keepalive_interval: Some(Duration::from_millis(200)),
+        keepalive_count_max: 2,
+        ..quiet_options()
+    };
+    let mut terminal = start_terminal(&server, options);
+    terminal.wait_connected().await;
+
+    // Long enough for several probes, all refused by the server.
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    terminal.control.send(Control::Close).await.unwrap();
+
+    assert_eq!(terminal.finish().await.unwrap(), SessionEnd::Closed);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn closing_does_not_wait_for_a_pending_keepalive() {
+    let server = TestServer::builder()
+        .shell(ShellBehaviour::Idle)
+        .failure(Failure::Unresponsive)
+        .start()
+        .await;
+
+    let options = SessionOptions {
+        keepalive_interval: Some(Duration::from_secs(3)),
+        ..quiet_options()
+    };
+    let mut terminal = start_terminal(&server, options);
+    terminal.wait_connected().await;
+
+    // The first probe was sent and won't be answered before it times out.
+    tokio::time::sleep(Duration::from_millis(3500)).await;
+    let start = std::time::Instant::now();
+    terminal.control.send(Control::Close).await.unwrap();
+
+    assert_eq!(terminal.finish().await.unwrap(), SessionEnd::Closed);
+    assert!(
+        start.elapsed() < Duration::from_millis(1500),
+        "closing waited for the keepalive"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn closing_does_not_wait_for_an_unresponsive_server() {
+    let server = TestServer::builder()
+        .shell(ShellBehaviour::Idle)
+        .failure(Failure::Unresponsive)
+        .start()
+        .await;
+
+    let mut terminal = start_terminal(&server, quiet_options());
+    terminal.wait_connected().await;
+
+    terminal.control.send(Control::Close).await.unwrap();
+
+    let end = tokio::time::timeout(Duration::from_secs(5), terminal.finish())
+        .await
+        .expect("closing waited for the server");
+    assert_eq!(end.unwrap(), SessionEnd::Closed);
+}
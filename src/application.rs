@@ -0,0 +1,198 @@
+use std::{
+    cell::{OnceCell, RefCell},
+    time::Duration,
+};
+
+use adw::subclass::prelude::*;
+use flatline_session::Control;
+use gtk::{gio, glib, prelude::*};
+use tokio::{sync::mpsc, task::JoinHandle};
+use tracing::{trace, warn};
+
+use crate::{
+    profile::{History, ProfileStore},
+    settings::Settings,
+};
+
+/// Longest wait for the sessions to disconnect when quitting.
+const QUIT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Session running on the runtime, kept so quitting can wait for it to disconnect.
+#[derive(Debug)]
+struct SessionTask {
+    /// Weak, so the pane dropping its sender still closes the session.
+    control: mpsc::WeakSender<Control>,
+    task: JoinHandle<()>,
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct Application {
+        /// Runtime shared by every ssh session of the application.
+        pub(super) runtime: RefCell<Option<tokio::runtime::Runtime>>,
+
+        pub(super) sessions: RefCell<Vec<SessionTask>>,
+
+        pub(super) profiles: OnceCell<ProfileStore>,
+
+        pub(super) history: OnceCell<History>,
+
+        pub(super) settings: OnceCell<Settings>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for Application {
+        const NAME: &'static str = "FlatLineApplication";
+        type Type = super::Application;
+        type ParentType = adw::Application;
+    }
+
+    impl ObjectImpl for Application {}
+
+    impl ApplicationImpl for Application {
+        fn startup(&self) {
+            self.parent_startup();
+
+            let runtime = tokio::runtime::Builder::new_multi_thread()
+                .thread_name("flatline-ssh")
+                .enable_all()
+                .build()
+                .expect("failed to build the tokio runtime");
+
+            self.runtime.replace(Some(runtime));
+
+            let settings = Settings::load(&Settings::default_path());
+            if self.settings.set(settings).is_err() {
+                warn!("the settings are already loaded");
+            }
+
+            let profiles = ProfileStore::load(ProfileStore::default_path());
+            if self.profiles.set(profiles).is_err() {
+                warn!("the profiles are already loaded");
+            }
+
+            let history = History::load(History::default_path());
+            if self.history.set(history).is_err() {
+                warn!("the history is already loaded");
+            }
+        }
+
+        fn shutdown(&self) {
+            let sessions = self.sessions.take();
+
+            if let Some(runtime) = self.runtime.take() {
+                // Nothing is left to draw, blocking here only delays the exit.
+                runtime.block_on(disconnect_all(sessions));
+                runtime.shutdown_background();
+            }
+
+            self.parent_shutdown();
+        }
+    }
+
+    impl GtkApplicationImpl for Application {}
+
+    impl AdwApplicationImpl for Application {}
+}
+
+glib::wrapper! {
+    pub struct Application(ObjectSubclass<imp::Application>)
+        @extends adw::Application, gtk::Application, gio::Application,
+        @implements gio::ActionGroup, gio::ActionMap;
+}
+
+impl Application {
+    pub fn new(application_id: &str) -> Self {
+        glib::Object::builder()
+            .property("application-id", application_id)
+            .build()
+    }
+
+    /// The running application, panics if called before it is created.
+    pub fn get() -> Self {
+        gio::Application::default()
+            .and_downcast()
+            .expect("the application is not a flatline application")
+    }
+
+    /// Keep track of a session spawned on the runtime, so quitting waits for it to disconnect.
+    pub fn track_session(&self, control: &mpsc::Sender<Control>, task: JoinHandle<()>) {
+        let mut sessions = self.imp().sessions.borrow_mut();
+        sessions.retain(|session| !session.task.is_finished());
+        sessions.push(SessionTask {
+            control: control.downgrade(),
+            task,
+        });
+    }
+
+    /// Settings read at startup, panics before startup.
+    pub fn settings(&self) -> Settings {
+        self.imp()
+            .settings
+            .get()
+            .expect("the application is not started")
+            .clone()
+    }
+
+    /// Saved connection profiles, panics before startup.
+    pub fn profiles(&self) -> ProfileStore {
+        self.imp()
+            .profiles
+            .get()
+            .expect("the application is not started")
+            .clone()
+    }
+
+    /// Connections recently made, panics before startup.
+    pub fn history(&self) -> History {
+        self.imp()
+            .history
+            .get()
+            .expect("the application is not started")
+            .clone()
+    }
+
+    /// Handle of the runtime ssh sessions are spawned on, panics outside of startup and shutdown.
+    pub fn runtime(&self) -> tokio::runtime::Handle {
+        self.imp()
+            .runtime
+            .borrow()
+            .as_ref()
+            .expect("the application is not started")
+            .handle()
+            .clone()
+    }
+}
+
+/// Ask every session still running to close, and wait for them for at most [`QUIT_TIMEOUT`].
+async fn disconnect_all(sessions: Vec<SessionTask>) {
+    let mut tasks = Vec::new();
+    for session in sessions {
+        if session.task.is_finished() {
+            continue;
+        }
+
+        // Sessions whose pane is already gone are closing on their own.
+        if let Some(control) = session.control.upgrade() {
+            if let Err(e) = control.try_send(Control::Close) {
+                warn!("failed to send close event : {}", e);
+            }
+        }
+        tasks.push(session.task);
+    }
+
+    trace!("waiting for {} sessions to disconnect", tasks.len());
+    let wait = async {
+        for task in tasks {
+            if let Err(e) = task.await {
+                warn!("session task failed : {}", e);
+            }
+        }
+    };
+
+    if tokio::time::timeout(QUIT_TIMEOUT, wait).await.is_err() {
+        warn!("some sessions did not disconnect in time");
+    }
+}
@@ -1,7 +0,0 @@
-pub(crate) type Result<T> = std::result::Result<T, Error>;
-
-#[derive(Debug, thiserror::Error)]
-pub(crate) enum Error {
-    #[error(transparent)]
-    IO(#[from] std::io::Error),
-}
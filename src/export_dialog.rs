@@ -0,0 +1,206 @@
+use std::{cell::RefCell, collections::BTreeSet};
+
+use adw::{prelude::*, subclass::prelude::*};
+use glib::clone;
+use gtk::{gio, glib, CompositeTemplate};
+use tracing::{error, trace};
+
+use crate::{
+    application::Application,
+    profile::{share, Profile},
+};
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(file = "gtk/export_dialog.ui")]
+    pub struct ExportDialog {
+        #[template_child]
+        groups_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        profiles_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        export_button: TemplateChild<gtk::Button>,
+
+        /// Saved profiles, with the check telling whether to export them.
+        profiles: RefCell<Vec<(Profile, gtk::CheckButton)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ExportDialog {
+        const NAME: &'static str = "FlatLineExportDialog";
+        type Type = super::ExportDialog;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ExportDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = &*self.obj();
+
+            let mut profiles: Vec<Profile> = Application::get()
+                .profiles()
+                .profiles()
+                .into_iter()
+                .filter(|profile| profile.source.is_none())
+                .collect();
+            profiles.sort_by_cached_key(|profile| profile.display_name().to_lowercase());
+
+            for profile in profiles {
+                let check = check_button();
+                check.connect_toggled(clone!(@weak obj => move |_| {
+                    obj.imp().update_export();
+                }));
+                let row = adw::ActionRow::builder()
+                    .title(glib::markup_escape_text(&profile.display_name()))
+                    .subtitle(glib::markup_escape_text(&profile.destination()))
+                    .activatable_widget(&check)
+                    .build();
+                row.add_prefix(&check);
+                self.profiles_group.add(&row);
+                self.profiles.borrow_mut().push((profile, check));
+            }
+
+            let groups: BTreeSet<String> = self
+                .profiles
+                .borrow()
+                .iter()
+                .filter_map(|(profile, _)| profile.group.clone())
+                .collect();
+            self.groups_group.set_visible(!groups.is_empty());
+            for group in groups {
+                let check = check_button();
+                // Selecting a group selects all of its connections.
+                check.connect_toggled(clone!(@weak obj, @strong group => move |check| {
+                    for (profile, profile_check) in obj.imp().profiles.borrow().iter() {
+                        if profile.group.as_ref() == Some(&group) {
+                            profile_check.set_active(check.is_active());
+                        }
+                    }
+                }));
+                let row = adw::ActionRow::builder()
+                    .title(glib::markup_escape_text(&group))
+                    .activatable_widget(&check)
+                    .build();
+                row.add_prefix(&check);
+                self.groups_group.add(&row);
+            }
+
+            self.update_export();
+            self.export_button
+                .connect_clicked(clone!(@weak obj => move |_| {
+                    glib::MainContext::default().spawn_local(clone!(@weak obj => async move {
+                        obj.imp().export().await;
+                    }));
+                }));
+        }
+    }
+
+    impl WidgetImpl for ExportDialog {}
+
+    impl WindowImpl for ExportDialog {}
+
+    impl AdwWindowImpl for ExportDialog {}
+
+    impl ExportDialog {
+        /// Profiles to export, with the settings they inherit as the file stands alone.
+        fn selected(&self) -> Vec<Profile> {
+            let store = Application::get().profiles();
+            self.profiles
+                .borrow()
+                .iter()
+                .filter(|(_, check)| check.is_active())
+                .map(|(profile, _)| Profile {
+                    parent: None,
+                    ..store.resolve(profile)
+                })
+                .collect()
+        }
+
+        fn update_export(&self) {
+            let any = self
+                .profiles
+                .borrow()
+                .iter()
+                .any(|(_, check)| check.is_active());
+            self.export_button.set_sensitive(any);
+        }
+
+        async fn export(&self) {
+            let filters = gio::ListStore::new::<gtk::FileFilter>();
+            for (name, pattern) in [("TOML", "*.toml"), ("JSON", "*.json")] {
+                let filter = gtk::FileFilter::new();
+                filter.set_name(Some(name));
+                filter.add_pattern(pattern);
+                filters.append(&filter);
+            }
+
+            let dialog = gtk::FileDialog::builder()
+                .title("Export Connections")
+                .initial_name("flatline-profiles.toml")
+                .filters(&filters)
+                .modal(true)
+                .build();
+
+            let obj = self.obj();
+            let path = match dialog.save_future(Some(&*obj)).await {
+                Ok(file) => file.path(),
+                Err(e) => {
+                    trace!("export cancelled : {}", e);
+                    return;
+                }
+            };
+            let Some(path) = path else {
+                return;
+            };
+
+            match share::export(&self.selected(), &path) {
+                Ok(()) => obj.close(),
+                Err(e) => {
+                    error!("failed to export the connections : {:#}", e);
+                    let dialog = adw::MessageDialog::new(
+                        Some(&*obj),
+                        Some("Failed to Export the Connections"),
+                        Some(&format!("{:#}", e)),
+                    );
+                    dialog.add_response("close", "_Close");
+                    dialog.present();
+                }
+            }
+        }
+    }
+}
+
+fn check_button() -> gtk::CheckButton {
+    gtk::CheckButton::builder()
+        .active(true)
+        .valign(gtk::Align::Center)
+        .build()
+}
+
+glib::wrapper! {
+    /// Dialog exporting saved profiles to a shareable file.
+    pub struct ExportDialog(ObjectSubclass<imp::ExportDialog>)
+        @extends adw::Window, gtk::Window, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Native,
+            gtk::Root, gtk::ShortcutManager;
+}
+
+impl ExportDialog {
+    /// Show the dialog over the window of `parent`.
+    pub fn present_for(parent: &gtk::Widget) {
+        let dialog: Self = glib::Object::new();
+        let window = parent.root().and_downcast::<gtk::Window>();
+        dialog.set_transient_for(window.as_ref());
+        dialog.present();
+    }
+}
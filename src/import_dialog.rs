@@ -0,0 +1,280 @@
+use std::cell::RefCell;
+
+use adw::{prelude::*, subclass::prelude::*};
+use glib::clone;
+use gtk::{glib, CompositeTemplate};
+use tracing::{error, trace};
+
+use crate::{
+    application::Application,
+    profile::{import::ImportSource, Profile},
+};
+
+/// Choices offered for a connection matching a saved one, in the order of the combo row.
+const CONFLICT_CHOICES: [&str; 3] = ["Skip", "Replace", "Keep Both"];
+const REPLACE: u32 = 1;
+const KEEP_BOTH: u32 = 2;
+
+/// Connection of the preview, and the row telling whether to import it.
+#[derive(Debug)]
+enum Candidate {
+    New {
+        profile: Profile,
+        check: gtk::CheckButton,
+    },
+    Conflict {
+        profile: Profile,
+        /// Id of the saved profile it matches.
+        existing: String,
+        row: adw::ComboRow,
+    },
+}
+
+impl Candidate {
+    /// Profile to save, `None` if it is skipped.
+    fn chosen(&self) -> Option<Profile> {
+        match self {
+            Candidate::New { profile, check } => check.is_active().then(|| profile.clone()),
+            Candidate::Conflict {
+                profile,
+                existing,
+                row,
+            } => match row.selected() {
+                REPLACE => Some(Profile {
+                    id: existing.clone(),
+                    ..profile.clone()
+                }),
+                KEEP_BOTH => Some(profile.clone()),
+                _ => None,
+            },
+        }
+    }
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(file = "gtk/import_dialog.ui")]
+    pub struct ImportDialog {
+        #[template_child]
+        navigation: TemplateChild<adw::NavigationView>,
+        #[template_child]
+        sources_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        next_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        preview_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        import_button: TemplateChild<gtk::Button>,
+
+        /// Switch of each source, with the connections read from it.
+        sources: RefCell<Vec<(adw::SwitchRow, Vec<Profile>)>>,
+
+        candidates: RefCell<Vec<Candidate>>,
+
+        /// Rows of the preview, removed when it is built again.
+        preview_rows: RefCell<Vec<gtk::Widget>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ImportDialog {
+        const NAME: &'static str = "FlatLineImportDialog";
+        type Type = super::ImportDialog;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ImportDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = &*self.obj();
+
+            for source in ImportSource::ALL {
+                let location = source.location();
+                let (profiles, subtitle) = match source.read() {
+                    Ok(profiles) => {
+                        let subtitle = match profiles.len() {
+                            0 => format!("No connection in {}", location.display()),
+                            1 => format!("1 connection in {}", location.display()),
+                            n => format!("{} connections in {}", n, location.display()),
+                        };
+                        (profiles, subtitle)
+                    }
+                    Err(e) => {
+                        trace!("nothing to import from {} : {:#}", source.label(), e);
+                        (Vec::new(), format!("{} not found", location.display()))
+                    }
+                };
+
+                let row = adw::SwitchRow::builder()
+                    .title(source.label())
+                    .subtitle(glib::markup_escape_text(&subtitle))
+                    .active(!profiles.is_empty())
+                    .sensitive(!profiles.is_empty())
+                    .build();
+                row.connect_active_notify(clone!(@weak obj => move |_| {
+                    obj.imp().update_next();
+                }));
+                self.sources_group.add(&row);
+                self.sources.borrow_mut().push((row, profiles));
+            }
+            self.update_next();
+
+            self.next_button
+                .connect_clicked(clone!(@weak obj => move |_| {
+                    let imp = obj.imp();
+                    imp.build_preview();
+                    imp.navigation.push_by_tag("preview");
+                }));
+
+            self.import_button
+                .connect_clicked(clone!(@weak obj => move |_| {
+                    obj.imp().import();
+                }));
+        }
+    }
+
+    impl WidgetImpl for ImportDialog {}
+
+    impl WindowImpl for ImportDialog {}
+
+    impl AdwWindowImpl for ImportDialog {}
+
+    impl ImportDialog {
+        fn update_next(&self) {
+            let any = self.sources.borrow().iter().any(|(row, _)| row.is_active());
+            self.next_button.set_sensitive(any);
+        }
+
+        fn build_preview(&self) {
+            for row in self.preview_rows.take() {
+                self.preview_group.remove(&row);
+            }
+
+            let store = Application::get().profiles();
+            let mut candidates = Vec::new();
+            let mut rows: Vec<gtk::Widget> = Vec::new();
+            let mut seen: Vec<Profile> = Vec::new();
+
+            for (switch, profiles) in self.sources.borrow().iter() {
+                if !switch.is_active() {
+                    continue;
+                }
+
+                for profile in profiles {
+                    // The same host is often configured in several clients.
+                    let duplicate = seen.iter().any(|p| {
+                        p.host == profile.host && p.port == profile.port && p.user == profile.user
+                    });
+                    if duplicate {
+                        continue;
+                    }
+                    seen.push(profile.clone());
+
+                    let title = glib::markup_escape_text(&profile.display_name());
+                    let destination = profile.destination();
+
+                    match store.find_conflict(profile) {
+                        Some(existing) => {
+                            let row = adw::ComboRow::builder()
+                                .title(title)
+                                .subtitle(glib::markup_escape_text(&format!(
+                                    "{} · matches {}",
+                                    destination,
+                                    existing.display_name()
+                                )))
+                                .model(&gtk::StringList::new(&CONFLICT_CHOICES))
+                                .build();
+                            rows.push(row.clone().upcast());
+                            candidates.push(Candidate::Conflict {
+                                profile: profile.clone(),
+                                existing: existing.id,
+                                row,
+                            });
+                        }
+                        None => {
+                            let check = gtk::CheckButton::builder()
+                                .active(true)
+                                .valign(gtk::Align::Center)
+                                .build();
+                            let row = adw::ActionRow::builder()
+                                .title(title)
+                                .subtitle(glib::markup_escape_text(&destination))
+                                .activatable_widget(&check)
+                                .build();
+                            row.add_prefix(&check);
+                            rows.push(row.upcast());
+                            candidates.push(Candidate::New {
+                                profile: profile.clone(),
+                                check,
+                            });
+                        }
+                    }
+                }
+            }
+
+            for row in &rows {
+                self.preview_group.add(row);
+            }
+            self.import_button.set_sensitive(!candidates.is_empty());
+            self.preview_rows.replace(rows);
+            self.candidates.replace(candidates);
+        }
+
+        fn import(&self) {
+            let profiles: Vec<Profile> = self
+                .candidates
+                .borrow()
+                .iter()
+                .filter_map(Candidate::chosen)
+                .collect();
+
+            let obj = self.obj();
+            if profiles.is_empty() {
+                obj.close();
+                return;
+            }
+
+            trace!("importing {} connections", profiles.len());
+            match Application::get().profiles().insert_all(profiles) {
+                Ok(()) => obj.close(),
+                Err(e) => {
+                    error!("failed to import the connections : {:#}", e);
+                    let dialog = adw::MessageDialog::new(
+                        Some(&*obj),
+                        Some("Failed to Import the Connections"),
+                        Some(&format!("{:#}", e)),
+                    );
+                    dialog.add_response("close", "_Close");
+                    dialog.present();
+                }
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Wizard importing the connections saved by other clients as profiles.
+    pub struct ImportDialog(ObjectSubclass<imp::ImportDialog>)
+        @extends adw::Window, gtk::Window, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Native,
+            gtk::Root, gtk::ShortcutManager;
+}
+
+impl ImportDialog {
+    /// Show the wizard over the window of `parent`.
+    pub fn present_for(parent: &gtk::Widget) {
+        let dialog: Self = glib::Object::new();
+        let window = parent.root().and_downcast::<gtk::Window>();
+        dialog.set_transient_for(window.as_ref());
+        dialog.present();
+    }
+}
@@ -1,22 +1,28 @@
-use adw::{prelude::*, Application, ApplicationWindow, TabBar, TabView, ToolbarView};
+use adw::{prelude::*, ApplicationWindow, TabBar, TabView, ToolbarView};
 use glib::clone;
 use gtk::{Button, WindowControls};
 
+use application::Application;
 use pane::Pane;
+use settings::LastTabPolicy;
 
-pub(crate) mod error;
+mod application;
+mod export_dialog;
+mod import_dialog;
 pub(crate) mod new_pane;
 mod pane;
+mod profile;
+mod profile_editor;
+mod quick_connect;
 pub mod remote_pane;
-mod ssh;
-pub(crate) mod util;
+mod settings;
 
 const APP_ID: &str = "fr.oupson.Flatline";
 
 fn main() -> glib::ExitCode {
     tracing_subscriber::fmt::init();
 
-    let app = Application::builder().application_id(APP_ID).build();
+    let app = Application::new(APP_ID);
     app.connect_activate(build_ui);
     app.run()
 }
@@ -61,6 +67,17 @@ fn build_ui(app: &Application) {
         .content(&content)
         .build();
 
+    tab_view.connect_n_pages_notify(clone!(@weak window, @weak app => move |tab_view| {
+        if tab_view.n_pages() > 0 {
+            return;
+        }
+
+        match app.settings().last_tab {
+            LastTabPolicy::CloseWindow => window.close(),
+            LastTabPolicy::KeepNewPane => append_pane(tab_view),
+        }
+    }));
+
     window.present();
 }
 
@@ -72,5 +89,17 @@ fn append_pane(tab_view: &TabView) {
         .sync_create()
         .build();
 
+    pane.bind_property("needs-attention", &page, "needs-attention")
+        .sync_create()
+        .build();
+
+    pane.bind_property("loading", &page, "loading")
+        .sync_create()
+        .build();
+
+    pane.bind_property("indicator-icon", &page, "indicator-icon")
+        .sync_create()
+        .build();
+
     tab_view.set_selected_page(&page);
 }
@@ -1,5 +1,16 @@
-use adw::subclass::prelude::*;
-use gtk::{gio, glib, prelude::*, CompositeTemplate};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashSet},
+};
+
+use adw::{prelude::*, subclass::prelude::*};
+use glib::clone;
+use gtk::{gdk, gio, glib, CompositeTemplate};
+
+use crate::{application::Application, profile::Profile, quick_connect::QuickConnect};
+
+/// Most recent connections shown.
+const RECENT_SHOWN: usize = 5;
 
 mod imp {
     use super::*;
@@ -7,12 +18,36 @@ mod imp {
     #[derive(Debug, Default, CompositeTemplate)]
     #[template(file = "gtk/new_pane.ui")]
     pub struct NewPane {
+        #[template_child]
+        quick_connect: TemplateChild<QuickConnect>,
+        #[template_child]
+        search: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        favourites_section: TemplateChild<gtk::Box>,
+        #[template_child]
+        favourites_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        recent_section: TemplateChild<gtk::Box>,
+        #[template_child]
+        recent_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        clear_history: TemplateChild<gtk::Button>,
         #[template_child]
         entry_list: TemplateChild<gtk::ListBox>,
         #[template_child]
         close_pane: TemplateChild<gtk::Button>,
         #[template_child]
         new_pane: TemplateChild<gtk::Button>,
+
+        /// Handlers refreshing the lists when the profiles or the history change, disconnected
+        /// on dispose.
+        signal_handlers: RefCell<Vec<(glib::Object, glib::SignalHandlerId)>>,
+
+        /// Groups collapsed by the user, kept while the list is rebuilt.
+        collapsed: RefCell<HashSet<String>>,
+
+        /// Best match of the search, connected to when it is activated.
+        first_match: RefCell<Option<adw::ActionRow>>,
     }
 
     #[glib::object_subclass]
@@ -22,6 +57,7 @@ mod imp {
         type ParentType = gtk::Widget;
 
         fn class_init(klass: &mut Self::Class) {
+            QuickConnect::ensure_type();
             Self::bind_template(klass);
 
             klass.set_layout_manager_type::<gtk::BinLayout>();
@@ -35,6 +71,10 @@ mod imp {
 
     impl ObjectImpl for NewPane {
         fn dispose(&self) {
+            for (object, handler) in self.signal_handlers.take() {
+                object.disconnect(handler);
+            }
+
             while let Some(child) = self.obj().first_child() {
                 child.unparent();
             }
@@ -42,10 +82,309 @@ mod imp {
 
         fn constructed(&self) {
             self.parent_constructed();
+            let obj = &*self.obj();
+
+            // Typing anywhere on the page searches, Enter connects to the best match.
+            self.search.set_key_capture_widget(Some(obj));
+            self.search
+                .connect_search_changed(clone!(@weak obj => move |_| {
+                    obj.imp().refresh();
+                }));
+            self.search.connect_activate(clone!(@weak obj => move |_| {
+                if let Some(row) = obj.imp().first_match.borrow().as_ref() {
+                    row.activate();
+                }
+            }));
+            self.search.connect_stop_search(|search| {
+                search.set_text("");
+            });
+
+            let keys = gtk::EventControllerKey::new();
+            keys.connect_key_pressed(clone!(@weak obj => @default-return glib::Propagation::Proceed, move |_, key, _, _| {
+                if key != gdk::Key::Down {
+                    return glib::Propagation::Proceed;
+                }
+
+                match obj.imp().first_row() {
+                    Some(row) => {
+                        row.grab_focus();
+                        glib::Propagation::Stop
+                    }
+                    None => glib::Propagation::Proceed,
+                }
+            }));
+            self.search.add_controller(keys);
+
+            let shortcuts = gtk::ShortcutController::new();
+            shortcuts.set_scope(gtk::ShortcutScope::Managed);
+            shortcuts.add_shortcut(gtk::Shortcut::new(
+                gtk::ShortcutTrigger::parse_string("<Control>f"),
+                Some(gtk::CallbackAction::new(
+                    clone!(@weak obj => @default-return glib::Propagation::Proceed, move |_, _| {
+                        obj.imp().search.grab_focus();
+                        glib::Propagation::Stop
+                    }),
+                )),
+            ));
+            obj.add_controller(shortcuts);
+
+            self.clear_history.connect_clicked(|_| {
+                Application::get().history().clear();
+            });
+
+            let app = Application::get();
+            let store = app.profiles();
+            let handler = store.connect_changed(clone!(@weak obj => move |_| {
+                obj.imp().refresh();
+            }));
+            let history = app.history();
+            let history_handler = history.connect_changed(clone!(@weak obj => move |_| {
+                obj.imp().refresh();
+            }));
+            self.signal_handlers.replace(vec![
+                (store.upcast(), handler),
+                (history.upcast(), history_handler),
+            ]);
+            self.refresh();
         }
     }
 
     impl WidgetImpl for NewPane {}
+
+    impl NewPane {
+        /// First row shown on the page.
+        fn first_row(&self) -> Option<gtk::ListBoxRow> {
+            [
+                (&*self.favourites_section, &*self.favourites_list),
+                (&*self.recent_section, &*self.recent_list),
+            ]
+            .into_iter()
+            .filter(|(section, _)| section.is_visible())
+            .find_map(|(_, list)| list.row_at_index(0))
+            .or_else(|| self.entry_list.row_at_index(0))
+        }
+
+        /// Rebuild the lists, the profiles are grouped, or sorted by relevance while searching.
+        fn refresh(&self) {
+            let query = self.search.text();
+            let query = query.trim();
+
+            self.refresh_pinned(query.is_empty());
+
+            self.entry_list.remove_all();
+            self.first_match.replace(None);
+
+            let placeholder = if query.is_empty() {
+                "No saved connection"
+            } else {
+                "No matching connection"
+            };
+            let placeholder = gtk::Label::builder()
+                .label(placeholder)
+                .margin_top(12)
+                .margin_bottom(12)
+                .css_classes(["dim-label"])
+                .build();
+            self.entry_list.set_placeholder(Some(&placeholder));
+
+            let mut profiles = Application::get().profiles().resolved_profiles();
+
+            if !query.is_empty() {
+                let mut matches: Vec<(i32, Profile)> = profiles
+                    .into_iter()
+                    .filter_map(|profile| Some((profile.search_score(query)?, profile)))
+                    .collect();
+                matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+                for (_, profile) in &matches {
+                    let row = profile_row(profile, true);
+                    self.entry_list.append(&row);
+                    if self.first_match.borrow().is_none() {
+                        self.first_match.replace(Some(row));
+                    }
+                }
+                return;
+            }
+
+            profiles.sort_by_cached_key(|profile| profile.display_name().to_lowercase());
+
+            let mut groups: BTreeMap<String, Vec<Profile>> = BTreeMap::new();
+            let mut ungrouped = Vec::new();
+            for profile in profiles {
+                match profile.group.clone() {
+                    Some(group) => groups.entry(group).or_default().push(profile),
+                    None => ungrouped.push(profile),
+                }
+            }
+
+            let obj = &*self.obj();
+            for (group, profiles) in groups {
+                let expander = adw::ExpanderRow::builder()
+                    .title(glib::markup_escape_text(&group))
+                    .subtitle(match profiles.len() {
+                        1 => "1 connection".to_owned(),
+                        n => format!("{} connections", n),
+                    })
+                    .expanded(!self.collapsed.borrow().contains(&group))
+                    .build();
+                let edit = gtk::Button::builder()
+                    .icon_name("document-edit-symbolic")
+                    .tooltip_text("Edit Group Defaults")
+                    .valign(gtk::Align::Center)
+                    .action_name("pane.edit-group")
+                    .action_target(&group.to_variant())
+                    .css_classes(["flat"])
+                    .build();
+                expander.add_suffix(&edit);
+                for profile in &profiles {
+                    expander.add_row(&profile_row(profile, false));
+                }
+
+                expander.connect_expanded_notify(clone!(@weak obj => move |expander| {
+                    let mut collapsed = obj.imp().collapsed.borrow_mut();
+                    if expander.is_expanded() {
+                        collapsed.remove(&group);
+                    } else {
+                        collapsed.insert(group.clone());
+                    }
+                }));
+                self.entry_list.append(&expander);
+            }
+
+            for profile in &ungrouped {
+                self.entry_list.append(&profile_row(profile, false));
+            }
+        }
+
+        /// Fill the favourites and the recent connections, hidden while searching.
+        fn refresh_pinned(&self, show: bool) {
+            self.favourites_list.remove_all();
+            self.recent_list.remove_all();
+
+            let app = Application::get();
+            let store = app.profiles();
+
+            let mut favourites: Vec<Profile> = store
+                .resolved_profiles()
+                .into_iter()
+                .filter(|profile| profile.favourite && profile.source.is_none())
+                .collect();
+            favourites.sort_by_cached_key(|profile| profile.display_name().to_lowercase());
+            for profile in &favourites {
+                self.favourites_list.append(&profile_row(profile, true));
+            }
+
+            let now = glib::DateTime::now_local()
+                .map(|now| now.to_unix())
+                .unwrap_or_default();
+            let mut recent = 0;
+            for entry in app.history().entries() {
+                if recent == RECENT_SHOWN {
+                    break;
+                }
+
+                let (title, action, target) = match &entry.profile {
+                    Some(id) => match store.get(id) {
+                        Some(profile) => {
+                            (profile.display_name(), "pane.connect-profile", profile.id)
+                        }
+                        // The profile was removed since.
+                        None => continue,
+                    },
+                    None => (
+                        entry.destination.clone(),
+                        "pane.quick-connect",
+                        entry.destination.clone(),
+                    ),
+                };
+
+                let row = adw::ActionRow::builder()
+                    .title(glib::markup_escape_text(&title))
+                    .subtitle(format_last_used(entry.last_used, now))
+                    .activatable(true)
+                    .action_name(action)
+                    .action_target(&target.to_variant())
+                    .build();
+                row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+                self.recent_list.append(&row);
+                recent += 1;
+            }
+
+            self.favourites_section
+                .set_visible(show && !favourites.is_empty());
+            self.recent_section.set_visible(show && recent > 0);
+        }
+    }
+}
+
+/// How long ago a connection was made, for the recent connections.
+fn format_last_used(last_used: i64, now: i64) -> String {
+    let elapsed = now.saturating_sub(last_used).max(0);
+    match elapsed {
+        0..=59 => "Just now".to_owned(),
+        60..=119 => "1 minute ago".to_owned(),
+        120..=3599 => format!("{} minutes ago", elapsed / 60),
+        3600..=7199 => "1 hour ago".to_owned(),
+        7200..=86_399 => format!("{} hours ago", elapsed / 3600),
+        _ => glib::DateTime::from_unix_local(last_used)
+            .and_then(|date| date.format("%e %b %Y"))
+            .map(|date| date.trim().to_owned())
+            .unwrap_or_default(),
+    }
+}
+
+/// Row connecting with `profile`, with buttons to edit it unless it is read-only.
+fn profile_row(profile: &Profile, show_group: bool) -> adw::ActionRow {
+    let mut details = vec![profile.destination()];
+    if let Some(group) = profile.group.clone().filter(|_| show_group) {
+        details.push(group);
+    }
+    if !profile.tags.is_empty() {
+        details.push(profile.tags.join(", "));
+    }
+    if let Some(source) = &profile.source {
+        details.push(source.clone());
+    }
+
+    let row = adw::ActionRow::builder()
+        .title(glib::markup_escape_text(&profile.display_name()))
+        .subtitle(glib::markup_escape_text(&details.join(" · ")))
+        .activatable(true)
+        .action_name("pane.connect-profile")
+        .action_target(&profile.id.to_variant())
+        .build();
+
+    if profile.source.is_some() {
+        row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+        return row;
+    }
+
+    let (icon, tooltip) = if profile.favourite {
+        ("starred-symbolic", "Remove from Favourites")
+    } else {
+        ("non-starred-symbolic", "Add to Favourites")
+    };
+    let favourite = gtk::Button::builder()
+        .icon_name(icon)
+        .tooltip_text(tooltip)
+        .valign(gtk::Align::Center)
+        .action_name("pane.toggle-favourite")
+        .action_target(&profile.id.to_variant())
+        .css_classes(["flat"])
+        .build();
+    row.add_suffix(&favourite);
+
+    let edit = gtk::Button::builder()
+        .icon_name("document-edit-symbolic")
+        .tooltip_text("Edit")
+        .valign(gtk::Align::Center)
+        .action_name("pane.edit-profile")
+        .action_target(&profile.id.to_variant())
+        .css_classes(["flat"])
+        .build();
+    row.add_suffix(&edit);
+    row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+    row
 }
 
 glib::wrapper! {
@@ -1,14 +1,26 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, rc::Rc};
 
 use adw::subclass::prelude::*;
+use anyhow::Context;
+use flatline_session::SessionOptions;
 use glib::clone;
-use gtk::{gio, glib, prelude::*};
+use gtk::{gdk, gio, glib, pango, prelude::*};
+
+use crate::{profile::Profile, remote_pane::RemotePane};
 
 mod imp {
     use gio::{ActionEntry, SimpleActionGroup};
-    use tracing::warn;
+    use tracing::{error, trace, warn};
 
-    use crate::{new_pane, remote_pane::RemotePane};
+    use crate::{
+        application::Application,
+        export_dialog::ExportDialog,
+        import_dialog::ImportDialog,
+        new_pane,
+        profile::{share, ssh_config, ImportMode, SourceConfig, SourceKind},
+        profile_editor::{EditorResult, ProfileEditor},
+        remote_pane::{ConnectionState, RemotePane},
+    };
 
     use super::*;
     #[derive(Debug, glib::Properties)]
@@ -16,12 +28,24 @@ mod imp {
     pub struct Pane {
         #[property(get, set)]
         title: RefCell<String>,
+
+        #[property(get, set)]
+        needs_attention: RefCell<bool>,
+
+        #[property(get, set)]
+        loading: RefCell<bool>,
+
+        #[property(get, set)]
+        indicator_icon: RefCell<Option<gio::Icon>>,
     }
 
     impl Default for Pane {
         fn default() -> Self {
             Self {
                 title: RefCell::new("New Pane".to_owned()),
+                needs_attention: RefCell::new(false),
+                loading: RefCell::new(false),
+                indicator_icon: RefCell::new(None),
             }
         }
     }
@@ -51,29 +75,130 @@ mod imp {
 
             let action_new_entry = ActionEntry::builder("new-entry")
                 .activate(clone!(@weak self_obj => move |_, _, _| {
-                    warn!("TODO");
+                    glib::MainContext::default().spawn_local(clone!(@weak self_obj => async move {
+                        self_obj.imp().edit_profile(None).await;
+                    }));
+                }))
+                .build();
+
+            let action_edit_profile = ActionEntry::builder("edit-profile")
+                .parameter_type(Some(glib::VariantTy::STRING))
+                .activate(clone!(@weak self_obj => move |_, _, param| {
+                    let Some(profile) = profile_param(param) else {
+                        return;
+                    };
+
+                    glib::MainContext::default().spawn_local(clone!(@weak self_obj => async move {
+                        self_obj.imp().edit_profile(Some(profile)).await;
+                    }));
+                }))
+                .build();
+
+            let action_edit_group = ActionEntry::builder("edit-group")
+                .parameter_type(Some(glib::VariantTy::STRING))
+                .activate(clone!(@weak self_obj => move |_, _, param| {
+                    let Some(group) = param.and_then(|param| param.str()).map(str::to_owned) else {
+                        warn!("edit-group activated without a group");
+                        return;
+                    };
 
-                    while let Some(child) = self_obj.first_child() {
-                        child.unparent();
+                    glib::MainContext::default().spawn_local(clone!(@weak self_obj => async move {
+                        self_obj.imp().edit_group(&group).await;
+                    }));
+                }))
+                .build();
+
+            let action_connect_profile = ActionEntry::builder("connect-profile")
+                .parameter_type(Some(glib::VariantTy::STRING))
+                .activate(clone!(@weak self_obj => move |_, _, param| {
+                    if let Some(profile) = profile_param(param) {
+                        self_obj.imp().connect_profile(&profile, true, None);
+                    }
+                }))
+                .build();
+
+            let action_toggle_favourite = ActionEntry::builder("toggle-favourite")
+                .parameter_type(Some(glib::VariantTy::STRING))
+                .activate(clone!(@weak self_obj => move |_, _, param| {
+                    let Some(mut profile) = profile_param(param) else {
+                        return;
+                    };
+
+                    profile.favourite = !profile.favourite;
+                    if let Err(e) = Application::get().profiles().insert(profile) {
+                        error!("failed to save the profile : {:#}", e);
+                        self_obj.imp().show_error("Failed to Save the Connection", &format!("{:#}", e));
                     }
+                }))
+                .build();
+
+            let action_quick_connect = ActionEntry::builder("quick-connect")
+                .parameter_type(Some(glib::VariantTy::STRING))
+                .activate(clone!(@weak self_obj => move |_, _, param| {
+                    let Some(destination) = param.and_then(|param| param.str()) else {
+                        warn!("quick-connect activated without a destination");
+                        return;
+                    };
+
+                    self_obj.imp().quick_connect(destination);
+                }))
+                .build();
 
-                    let remote_pane = RemotePane::builder()
-                        .hexpand(true)
-                        .vexpand(true)
-                        .server_addr("localhost")
-                        .server_port(22)
-                        .build();
+            let action_import = ActionEntry::builder("import")
+                .activate(clone!(@weak self_obj => move |_, _, _| {
+                    ImportDialog::present_for(self_obj.upcast_ref());
+                }))
+                .build();
+
+            let action_import_file = ActionEntry::builder("import-file")
+                .activate(clone!(@weak self_obj => move |_, _, _| {
+                    glib::MainContext::default().spawn_local(clone!(@weak self_obj => async move {
+                        self_obj.imp().import_file().await;
+                    }));
+                }))
+                .build();
+
+            let action_add_inventory = ActionEntry::builder("add-inventory")
+                .activate(clone!(@weak self_obj => move |_, _, _| {
+                    glib::MainContext::default().spawn_local(clone!(@weak self_obj => async move {
+                        self_obj.imp().add_inventory().await;
+                    }));
+                }))
+                .build();
+
+            let action_export = ActionEntry::builder("export")
+                .activate(clone!(@weak self_obj => move |_, _, _| {
+                    ExportDialog::present_for(self_obj.upcast_ref());
+                }))
+                .build();
 
-                    remote_pane.bind_property("title", &self_obj, "title")
-                        .sync_create()
-                        .build();
+            let action_close = ActionEntry::builder("close")
+                .activate(clone!(@weak self_obj => move |_, _, _| {
+                    let tab_view = self_obj
+                        .ancestor(adw::TabView::static_type())
+                        .and_downcast::<adw::TabView>();
 
-                    remote_pane.set_parent(&self_obj);
+                    if let Some(tab_view) = tab_view {
+                        let page = tab_view.page(&self_obj);
+                        tab_view.close_page(&page);
+                    }
                 }))
                 .build();
 
             let actions = SimpleActionGroup::new();
-            actions.add_action_entries([action_new_entry]);
+            actions.add_action_entries([
+                action_new_entry,
+                action_edit_profile,
+                action_edit_group,
+                action_connect_profile,
+                action_toggle_favourite,
+                action_quick_connect,
+                action_import,
+                action_import_file,
+                action_add_inventory,
+                action_export,
+                action_close,
+            ]);
             self_obj.insert_action_group("pane", Some(&actions));
 
             let new_pane = new_pane::NewPane::new();
@@ -82,6 +207,243 @@ mod imp {
     }
 
     impl WidgetImpl for Pane {}
+
+    /// Saved profile whose id is `param`.
+    fn profile_param(param: Option<&glib::Variant>) -> Option<Profile> {
+        let Some(id) = param.and_then(|param| param.str()) else {
+            warn!("profile action activated without a profile id");
+            return None;
+        };
+
+        let profile = Application::get().profiles().get(id);
+        if profile.is_none() {
+            warn!("no profile with id {}", id);
+        }
+        profile
+    }
+
+    impl Pane {
+        async fn edit_profile(&self, profile: Option<Profile>) {
+            let self_obj = &*self.obj();
+
+            match ProfileEditor::run(self_obj.upcast_ref(), profile).await {
+                Some(EditorResult::Save(profile)) => {
+                    if let Err(e) = Application::get().profiles().insert(profile) {
+                        error!("failed to save the profile : {:#}", e);
+                        self.show_error("Failed to Save the Connection", &format!("{:#}", e));
+                    }
+                }
+                Some(EditorResult::ConnectOnce(profile)) => {
+                    self.connect_profile(&profile, false, None)
+                }
+                None => trace!("profile edition cancelled"),
+            }
+        }
+
+        /// Edit the settings the profiles of `group` inherit.
+        async fn edit_group(&self, group: &str) {
+            let store = Application::get().profiles();
+            let defaults = store.group_defaults(group);
+
+            let Some(defaults) =
+                ProfileEditor::run_group(self.obj().upcast_ref(), group, defaults).await
+            else {
+                trace!("group edition cancelled");
+                return;
+            };
+            if let Err(e) = store.set_group_defaults(group, defaults) {
+                error!("failed to save the group defaults : {:#}", e);
+                self.show_error("Failed to Save the Group", &format!("{:#}", e));
+            }
+        }
+
+        /// Connect with `profile` and the settings it inherits, recorded in the history as
+        /// `destination`, by default its own, once connected. Only a `saved` profile is recorded
+        /// under its id, others are recorded like quick connections.
+        fn connect_profile(&self, profile: &Profile, saved: bool, destination: Option<&str>) {
+            let resolved = Application::get().profiles().resolve(profile);
+            let destination = destination
+                .map(str::to_owned)
+                .unwrap_or_else(|| resolved.destination());
+            match super::remote_pane_for(&resolved) {
+                Ok(remote_pane) => {
+                    let saved = saved
+                        .then(|| Application::get().profiles().get(&profile.id))
+                        .flatten()
+                        .map(|profile| profile.id);
+
+                    // Only the first connection is recorded, not the automatic reconnections.
+                    let handler = Rc::new(RefCell::new(None));
+                    let id = remote_pane.connect_state_notify(
+                        clone!(@strong handler => move |remote_pane| {
+                            if remote_pane.state() != ConnectionState::Connected {
+                                return;
+                            }
+                            Application::get()
+                                .history()
+                                .record(&destination, saved.as_deref());
+                            if let Some(id) = handler.take() {
+                                remote_pane.disconnect(id);
+                            }
+                        }),
+                    );
+                    handler.replace(Some(id));
+
+                    self.show_remote_pane(remote_pane);
+                }
+                Err(e) => {
+                    error!("failed to open {} : {:#}", profile.display_name(), e);
+                    self.show_error("Invalid Connection", &format!("{:#}", e));
+                }
+            }
+        }
+
+        fn quick_connect(&self, destination: &str) {
+            let destination = destination.trim();
+            match Profile::from_destination(destination, &ssh_config::user_hosts()) {
+                Ok(profile) => self.connect_profile(&profile, false, Some(destination)),
+                Err(e) => self.show_error("Invalid Destination", &format!("{:#}", e)),
+            }
+        }
+
+        /// Import the profiles of a shared file chosen by the user, or keep them in sync with it.
+        async fn import_file(&self) {
+            let self_obj = &*self.obj();
+            let window = self_obj.root().and_downcast::<gtk::Window>();
+
+            let dialog = gtk::FileDialog::builder()
+                .title("Import Connections")
+                .modal(true)
+                .build();
+            let path = match dialog.open_future(window.as_ref()).await {
+                Ok(file) => file.path(),
+                Err(e) => {
+                    trace!("import cancelled : {}", e);
+                    return;
+                }
+            };
+            let Some(path) = path else {
+                return;
+            };
+
+            let profiles = match share::read(&path) {
+                Ok(profiles) => profiles,
+                Err(e) => {
+                    self.show_error("Failed to Read the Connections", &format!("{:#}", e));
+                    return;
+                }
+            };
+
+            let body = format!(
+                "{} holds {} connections. Merge them with the saved ones, replace the saved ones, or list them as they are in the file, following its changes.",
+                path.display(),
+                profiles.len()
+            );
+            let dialog =
+                adw::MessageDialog::new(window.as_ref(), Some("Import Connections"), Some(&body));
+            dialog.add_responses(&[
+                ("cancel", "_Cancel"),
+                ("sync", "_Keep in Sync"),
+                ("replace", "_Replace"),
+                ("merge", "_Merge"),
+            ]);
+            dialog.set_response_appearance("replace", adw::ResponseAppearance::Destructive);
+            dialog.set_response_appearance("merge", adw::ResponseAppearance::Suggested);
+            dialog.set_close_response("cancel");
+            dialog.set_default_response(Some("merge"));
+
+            let store = Application::get().profiles();
+            let res = match dialog.choose_future().await.as_str() {
+                "merge" => store.import(profiles, ImportMode::Merge),
+                "replace" => store.import(profiles, ImportMode::Replace),
+                "sync" => store.add_source(SourceConfig {
+                    path,
+                    kind: SourceKind::Shared,
+                }),
+                _ => return,
+            };
+            if let Err(e) = res {
+                error!("failed to import the connections : {:#}", e);
+                self.show_error("Failed to Import the Connections", &format!("{:#}", e));
+            }
+        }
+
+        /// List the hosts of an Ansible inventory chosen by the user.
+        async fn add_inventory(&self) {
+            let window = self.obj().root().and_downcast::<gtk::Window>();
+
+            let dialog = gtk::FileDialog::builder()
+                .title("Add Ansible Inventory")
+                .modal(true)
+                .build();
+            let path = match dialog.open_future(window.as_ref()).await {
+                Ok(file) => file.path(),
+                Err(e) => {
+                    trace!("no inventory chosen : {}", e);
+                    return;
+                }
+            };
+            let Some(path) = path else {
+                return;
+            };
+
+            let res = Application::get().profiles().add_source(SourceConfig {
+                path,
+                kind: SourceKind::Ansible,
+            });
+            if let Err(e) = res {
+                error!("failed to add the inventory : {:#}", e);
+                self.show_error("Failed to Add the Inventory", &format!("{:#}", e));
+            }
+        }
+
+        fn show_error(&self, heading: &str, body: &str) {
+            let window = self.obj().root().and_downcast::<gtk::Window>();
+            let dialog = adw::MessageDialog::new(window.as_ref(), Some(heading), Some(body));
+            dialog.add_response("close", "_Close");
+            dialog.present();
+        }
+
+        /// Replace the content of the pane with `remote_pane`, follow its state and start it.
+        fn show_remote_pane(&self, remote_pane: RemotePane) {
+            let self_obj = &*self.obj();
+
+            while let Some(child) = self_obj.first_child() {
+                child.unparent();
+            }
+
+            remote_pane
+                .bind_property("title", self_obj, "title")
+                .sync_create()
+                .build();
+
+            remote_pane
+                .bind_property("state", self_obj, "needs-attention")
+                .transform_to(|_, state: ConnectionState| Some(state.needs_attention()))
+                .sync_create()
+                .build();
+
+            remote_pane
+                .bind_property("state", self_obj, "loading")
+                .transform_to(|_, state: ConnectionState| Some(state.is_loading()))
+                .sync_create()
+                .build();
+
+            remote_pane
+                .bind_property("state", self_obj, "indicator-icon")
+                .transform_to(|_, state: ConnectionState| {
+                    let icon = state
+                        .icon_name()
+                        .map(|name| gio::ThemedIcon::new(name).upcast::<gio::Icon>());
+                    Some(icon)
+                })
+                .sync_create()
+                .build();
+
+            remote_pane.set_parent(self_obj);
+            remote_pane.start();
+        }
+    }
 }
 
 glib::wrapper! {
@@ -101,3 +463,47 @@ impl Pane {
         Self::default()
     }
 }
+
+/// Build the remote pane connecting with `profile`.
+fn remote_pane_for(profile: &Profile) -> anyhow::Result<RemotePane> {
+    let connect = profile.connect_options()?;
+
+    let mut env = SessionOptions::default().env;
+    env.extend(profile.env());
+
+    let mut builder = RemotePane::builder()
+        .hexpand(true)
+        .vexpand(true)
+        .server_addr(connect.host.as_str())
+        .server_port(connect.port)
+        .auth(connect.auth)
+        .jump_hosts(connect.jump_hosts)
+        .env(env);
+
+    if let Some(user) = connect.user {
+        builder = builder.user(user);
+    }
+    if let Some(command) = connect.proxy_command {
+        builder = builder.proxy_command(command);
+    }
+
+    let appearance = &profile.appearance;
+    if let Some(font) = &appearance.font {
+        builder = builder.font(&pango::FontDescription::from_string(font));
+    }
+    if let Some(color) = &appearance.foreground {
+        builder = builder.foreground_color(&parse_color(color)?);
+    }
+    if let Some(color) = &appearance.background {
+        builder = builder.background_color(&parse_color(color)?);
+    }
+    if let Some(color) = &appearance.stderr_color {
+        builder = builder.stderr_color(&parse_color(color)?);
+    }
+
+    Ok(builder.build())
+}
+
+fn parse_color(color: &str) -> anyhow::Result<gdk::RGBA> {
+    gdk::RGBA::parse(color).with_context(|| format!("invalid color {}", color))
+}
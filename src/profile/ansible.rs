@@ -0,0 +1,826 @@
+//! Ansible inventories, in the INI or YAML format, read as profiles.
+//!
+//! Each host becomes a profile connecting with `ansible_host`, `ansible_port`, `ansible_user`
+//! and `ansible_ssh_private_key_file`, set on the host or inherited from its groups. The
+//! deepest group of a host is its group, the others are its tags.
+//!
+//! Variables also come from the `group_vars` and `host_vars` directories next to the
+//! inventory, which take precedence over the ones of the inventory itself. Those of a playbook
+//! directory and encrypted vault files are not read.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use serde_yaml::Value;
+use tracing::{trace, warn};
+
+use super::{AuthKind, Profile};
+
+/// Groups every inventory has, not shown.
+const IMPLICIT_GROUPS: [&str; 2] = ["all", "ungrouped"];
+
+/// Most hosts a pattern with ranges expands to, larger ones are skipped.
+const MAX_EXPANDED_HOSTS: usize = 4096;
+
+/// Values of `ansible_connection` going through ssh, other hosts are skipped.
+const SSH_CONNECTIONS: [&str; 4] = ["ssh", "smart", "paramiko", "paramiko_ssh"];
+
+type Vars = BTreeMap<String, String>;
+
+#[derive(Debug, Default)]
+struct Group {
+    name: String,
+    hosts: Vec<String>,
+    children: Vec<String>,
+    vars: Vars,
+}
+
+#[derive(Debug, Default)]
+struct Inventory {
+    /// In the order of the file.
+    groups: Vec<Group>,
+    /// In the order of the file.
+    hosts: Vec<String>,
+    host_vars: BTreeMap<String, Vars>,
+}
+
+impl Inventory {
+    fn group_mut(&mut self, name: &str) -> &mut Group {
+        let index = match self.groups.iter().position(|group| group.name == name) {
+            Some(index) => index,
+            None => {
+                self.groups.push(Group {
+                    name: name.to_owned(),
+                    ..Group::default()
+                });
+                self.groups.len() - 1
+            }
+        };
+        &mut self.groups[index]
+    }
+
+    fn add_host(&mut self, group: &str, host: &str, vars: Vars) {
+        let group = self.group_mut(group);
+        if !group.hosts.iter().any(|h| h == host) {
+            group.hosts.push(host.to_owned());
+        }
+
+        if !self.hosts.iter().any(|h| h == host) {
+            self.hosts.push(host.to_owned());
+        }
+        self.host_vars
+            .entry(host.to_owned())
+            .or_default()
+            .extend(vars);
+    }
+
+    fn add_child(&mut self, group: &str, child: &str) {
+        self.group_mut(child);
+        let group = self.group_mut(group);
+        if !group.children.iter().any(|c| c == child) {
+            group.children.push(child.to_owned());
+        }
+    }
+
+    /// Add the variables of the `group_vars` and `host_vars` directories in `dir`.
+    fn read_vars_dirs(&mut self, dir: &Path) {
+        let group_vars = dir.join("group_vars");
+        if group_vars.is_dir() {
+            for name in IMPLICIT_GROUPS {
+                let vars = read_vars(&group_vars, name);
+                if !vars.is_empty() {
+                    self.group_mut(name).vars.extend(vars);
+                }
+            }
+            for group in &mut self.groups {
+                if !IMPLICIT_GROUPS.contains(&group.name.as_str()) {
+                    group.vars.extend(read_vars(&group_vars, &group.name));
+                }
+            }
+        }
+
+        let host_vars = dir.join("host_vars");
+        if host_vars.is_dir() {
+            for host in &self.hosts {
+                let vars = read_vars(&host_vars, host);
+                self.host_vars.entry(host.clone()).or_default().extend(vars);
+            }
+        }
+    }
+
+    /// Distance of `group` from the top of the inventory, parents come before their children.
+    fn depth(&self, group: &str, seen: &mut HashSet<String>) -> usize {
+        if !seen.insert(group.to_owned()) {
+            // The children form a cycle.
+            return 0;
+        }
+
+        let depth = self
+            .groups
+            .iter()
+            .filter(|parent| parent.children.iter().any(|c| c == group))
+            .map(|parent| self.depth(&parent.name, seen) + 1)
+            .max()
+            .unwrap_or(0);
+        seen.remove(group);
+        depth
+    }
+
+    /// Groups `host` belongs to, directly or through their parents, sorted by depth then name.
+    fn groups_of(&self, host: &str) -> Vec<(usize, &Group)> {
+        let mut names: Vec<&str> = self
+            .groups
+            .iter()
+            .filter(|group| group.hosts.iter().any(|h| h == host))
+            .map(|group| group.name.as_str())
+            .collect();
+
+        let mut i = 0;
+        while i < names.len() {
+            let name = names[i];
+            for parent in &self.groups {
+                if parent.children.iter().any(|c| c == name) && !names.contains(&&*parent.name) {
+                    names.push(&parent.name);
+                }
+            }
+            i += 1;
+        }
+
+        let mut groups: Vec<(usize, &Group)> = self
+            .groups
+            .iter()
+            .filter(|group| names.contains(&&*group.name))
+            .map(|group| (self.depth(&group.name, &mut HashSet::new()), group))
+            .collect();
+        groups.sort_by(|(a, a_group), (b, b_group)| a.cmp(b).then(a_group.name.cmp(&b_group.name)));
+        groups
+    }
+
+    fn profiles(&self, path: &Path) -> Vec<Profile> {
+        let all_vars = self
+            .groups
+            .iter()
+            .find(|group| group.name == "all")
+            .map(|group| group.vars.clone())
+            .unwrap_or_default();
+
+        let mut profiles = Vec::new();
+        for host in &self.hosts {
+            let groups = self.groups_of(host);
+
+            // Closer variables take precedence, as in Ansible.
+            let mut vars = all_vars.clone();
+            for (_, group) in &groups {
+                vars.extend(group.vars.clone());
+            }
+            if let Some(host_vars) = self.host_vars.get(host) {
+                vars.extend(host_vars.clone());
+            }
+
+            if var(&vars, &["ansible_connection"])
+                .is_some_and(|connection| !SSH_CONNECTIONS.contains(&connection))
+            {
+                continue;
+            }
+
+            let groups: Vec<(usize, &Group)> = groups
+                .into_iter()
+                .filter(|(_, group)| !IMPLICIT_GROUPS.contains(&group.name.as_str()))
+                .collect();
+            // The deepest group the host is listed in, the first by name on a tie.
+            let group = groups
+                .iter()
+                .rev()
+                .filter(|(_, group)| group.hosts.iter().any(|h| h == host))
+                .max_by_key(|(depth, _)| *depth)
+                .map(|(_, group)| group.name.clone());
+            let tags = groups
+                .iter()
+                .map(|(_, group)| group.name.clone())
+                .filter(|name| Some(name) != group.as_ref())
+                .collect();
+
+            let mut profile = Profile {
+                id: format!("ansible:{}:{}", path.display(), host),
+                name: host.clone(),
+                group,
+                tags,
+                host: var(&vars, &["ansible_host", "ansible_ssh_host"])
+                    .unwrap_or(host.as_str())
+                    .to_owned(),
+                user: var(&vars, &["ansible_user", "ansible_ssh_user"]).map(str::to_owned),
+                jump_hosts: var(
+                    &vars,
+                    &["ansible_ssh_common_args", "ansible_ssh_extra_args"],
+                )
+                .map(proxy_jump)
+                .unwrap_or_default(),
+                ..Profile::default()
+            };
+            if let Some(port) =
+                var(&vars, &["ansible_port", "ansible_ssh_port"]).and_then(|port| port.parse().ok())
+            {
+                profile.port = port;
+            }
+            if let Some(file) = var(&vars, &["ansible_ssh_private_key_file"]) {
+                profile.auth = AuthKind::IdentityFile;
+                profile.identity_file = Some(PathBuf::from(file));
+            }
+            profiles.push(profile);
+        }
+        profiles
+    }
+}
+
+/// First of `names` set in `vars`.
+fn var<'a>(vars: &'a Vars, names: &[&str]) -> Option<&'a str> {
+    names
+        .iter()
+        .find_map(|name| vars.get(*name))
+        .map(String::as_str)
+}
+
+/// Jump hosts set in ssh arguments, with `-J` or `-o ProxyJump=`.
+fn proxy_jump(args: &str) -> Vec<String> {
+    let words = split_words(args);
+    let mut words = words.iter();
+    while let Some(word) = words.next() {
+        let value = match word.as_str() {
+            "-J" | "-o" => words.next().map(String::as_str),
+            _ => word.strip_prefix("-J").or_else(|| word.strip_prefix("-o")),
+        };
+        let Some(value) = value else {
+            continue;
+        };
+        let value = if word.starts_with("-o") {
+            match value.split_once(['=', ' ']) {
+                Some((key, value)) if key.eq_ignore_ascii_case("ProxyJump") => value.trim(),
+                _ => continue,
+            }
+        } else {
+            value
+        };
+        return value
+            .split(',')
+            .map(str::trim)
+            .filter(|jump| !jump.is_empty() && *jump != "none")
+            .map(str::to_owned)
+            .collect();
+    }
+    Vec::new()
+}
+
+/// Read the hosts of the inventory at `path`, as YAML if its extension is `.yml` or `.yaml`,
+/// INI otherwise.
+pub fn read(path: &Path) -> anyhow::Result<Vec<Profile>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+
+    let is_yaml = path
+        .extension()
+        .is_some_and(|extension| extension == "yml" || extension == "yaml");
+    let mut inventory = if is_yaml {
+        parse_yaml(&text).with_context(|| format!("failed to parse {}", path.display()))?
+    } else {
+        parse_ini(&text)
+    };
+    if let Some(dir) = path.parent() {
+        inventory.read_vars_dirs(dir);
+    }
+    Ok(inventory.profiles(path))
+}
+
+/// Directories the variables of the inventory at `path` are read from : its `group_vars` and
+/// `host_vars` directories, whether they exist or not, and their subdirectories.
+pub fn vars_dirs(path: &Path) -> Vec<PathBuf> {
+    let Some(dir) = path.parent() else {
+        return Vec::new();
+    };
+
+    let mut dirs = Vec::new();
+    for name in ["group_vars", "host_vars"] {
+        let vars_dir = dir.join(name);
+        if let Ok(entries) = std::fs::read_dir(&vars_dir) {
+            dirs.extend(
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.is_dir()),
+            );
+        }
+        dirs.push(vars_dir);
+    }
+    dirs
+}
+
+/// Variables of the group or host `name` in a `group_vars` or `host_vars` directory, from the
+/// file named after it, with or without a YAML or JSON extension, or from every file of the
+/// directory named after it.
+fn read_vars(dir: &Path, name: &str) -> Vars {
+    let mut files: Vec<PathBuf> = ["", ".yml", ".yaml", ".json"]
+        .iter()
+        .map(|extension| dir.join(format!("{}{}", name, extension)))
+        .filter(|path| path.is_file())
+        .collect();
+
+    let sub_dir = dir.join(name);
+    if sub_dir.is_dir() {
+        match std::fs::read_dir(&sub_dir) {
+            Ok(entries) => {
+                let mut paths: Vec<PathBuf> = entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.is_file())
+                    .collect();
+                paths.sort();
+                files.extend(paths);
+            }
+            Err(e) => warn!("failed to read {} : {}", sub_dir.display(), e),
+        }
+    }
+
+    let mut vars = Vars::new();
+    for file in files {
+        let parsed = std::fs::read_to_string(&file)
+            .map_err(anyhow::Error::from)
+            .and_then(|text| serde_yaml::from_str::<Value>(&text).map_err(anyhow::Error::from));
+        match parsed {
+            Ok(value) => {
+                trace!("read variables of {} from {}", name, file.display());
+                vars.extend(yaml_vars(&value));
+            }
+            Err(e) => warn!("failed to read {} : {:#}", file.display(), e),
+        }
+    }
+    vars
+}
+
+fn parse_ini(text: &str) -> Inventory {
+    enum Section {
+        Hosts,
+        Vars,
+        Children,
+    }
+
+    let mut inventory = Inventory::default();
+    let mut group = "ungrouped".to_owned();
+    let mut section = Section::Hosts;
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let (name, kind) = match header.rsplit_once(':') {
+                Some((name, "vars")) => (name, Section::Vars),
+                Some((name, "children")) => (name, Section::Children),
+                _ => (header, Section::Hosts),
+            };
+            group = name.trim().to_owned();
+            section = kind;
+            inventory.group_mut(&group);
+            continue;
+        }
+
+        match section {
+            Section::Hosts => {
+                let words = split_words(line);
+                let Some((pattern, assignments)) = words.split_first() else {
+                    continue;
+                };
+                let vars: Vars = assignments
+                    .iter()
+                    .filter_map(|word| word.split_once('='))
+                    .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                    .collect();
+
+                // `host:port`, unless it is an IPv6 address.
+                let (pattern, port) = match pattern.split_once(':') {
+                    Some((host, port)) if !port.contains(':') && port.parse::<u16>().is_ok() => {
+                        (host, Some(port))
+                    }
+                    _ => (pattern.as_str(), None),
+                };
+                for host in expand(pattern) {
+                    let mut vars = vars.clone();
+                    if let Some(port) = port {
+                        vars.entry("ansible_port".to_owned())
+                            .or_insert_with(|| port.to_owned());
+                    }
+                    inventory.add_host(&group, &host, vars);
+                }
+            }
+            Section::Vars => {
+                if let Some((key, value)) = line.split_once('=') {
+                    let value = split_words(value).join(" ");
+                    inventory
+                        .group_mut(&group)
+                        .vars
+                        .insert(key.trim().to_owned(), value);
+                }
+            }
+            Section::Children => inventory.add_child(&group, line),
+        }
+    }
+    inventory
+}
+
+fn parse_yaml(text: &str) -> anyhow::Result<Inventory> {
+    let mut inventory = Inventory::default();
+    let root: Value = serde_yaml::from_str(text)?;
+    if let Some(groups) = root.as_mapping() {
+        for (name, group) in groups {
+            if let Some(name) = scalar(name) {
+                add_yaml_group(&mut inventory, &name, group);
+            }
+        }
+    }
+    Ok(inventory)
+}
+
+fn add_yaml_group(inventory: &mut Inventory, name: &str, group: &Value) {
+    inventory.group_mut(name);
+
+    if let Some(hosts) = group.get("hosts").and_then(Value::as_mapping) {
+        for (pattern, vars) in hosts {
+            let Some(pattern) = scalar(pattern) else {
+                continue;
+            };
+            for host in expand(&pattern) {
+                inventory.add_host(name, &host, yaml_vars(vars));
+            }
+        }
+    }
+
+    let vars = group.get("vars").map(yaml_vars).unwrap_or_default();
+    inventory.group_mut(name).vars.extend(vars);
+
+    if let Some(children) = group.get("children").and_then(Value::as_mapping) {
+        for (child, definition) in children {
+            if let Some(child) = scalar(child) {
+                inventory.add_child(name, &child);
+                add_yaml_group(inventory, &child, definition);
+            }
+        }
+    }
+}
+
+fn yaml_vars(vars: &Value) -> Vars {
+    let Some(vars) = vars.as_mapping() else {
+        return Vars::new();
+    };
+    vars.iter()
+        .filter_map(|(key, value)| Some((scalar(key)?, scalar(value)?)))
+        .collect()
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Hosts of a pattern with ranges, such as `web[01:20].example.com` or `db-[a:c]`, none if
+/// there would be more than [`MAX_EXPANDED_HOSTS`].
+fn expand(pattern: &str) -> Vec<String> {
+    let range = pattern.split_once('[').and_then(|(prefix, rest)| {
+        let (range, suffix) = rest.split_once(']')?;
+        let mut bounds = range.split(':');
+        let (start, end) = (bounds.next()?, bounds.next()?);
+        let step = bounds.next().map_or(Some(1), |step| step.parse().ok())?;
+        Some((prefix, start, end, step.max(1), suffix))
+    });
+    let Some((prefix, start, end, step, suffix)) = range else {
+        return vec![pattern.to_owned()];
+    };
+
+    let items: Vec<String> = match (start.parse::<u64>(), end.parse::<u64>()) {
+        (Ok(first), Ok(last)) => {
+            if last.saturating_sub(first) / step as u64 >= MAX_EXPANDED_HOSTS as u64 {
+                warn!("skipping {}, it has too many hosts", pattern);
+                return Vec::new();
+            }
+            // A leading zero pads the numbers to the width of the start.
+            let width = if start.starts_with('0') {
+                start.len()
+            } else {
+                0
+            };
+            (first..=last)
+                .step_by(step)
+                .map(|n| format!("{:0width$}", n, width = width))
+                .collect()
+        }
+        _ => {
+            let mut start_chars = start.chars();
+            let mut end_chars = end.chars();
+            match (
+                start_chars.next(),
+                start_chars.next(),
+                end_chars.next(),
+                end_chars.next(),
+            ) {
+                (Some(first), None, Some(last), None) => {
+                    if (last as u64).saturating_sub(first as u64) / step as u64
+                        >= MAX_EXPANDED_HOSTS as u64
+                    {
+                        warn!("skipping {}, it has too many hosts", pattern);
+                        return Vec::new();
+                    }
+                    (first..=last).step_by(step).map(String::from).collect()
+                }
+                _ => return vec![pattern.to_owned()],
+            }
+        }
+    };
+
+    let rests = expand(suffix);
+    if items.len().saturating_mul(rests.len()) > MAX_EXPANDED_HOSTS {
+        warn!("skipping {}, it has too many hosts", pattern);
+        return Vec::new();
+    }
+
+    items
+        .iter()
+        .flat_map(|item| {
+            rests
+                .iter()
+                .map(move |rest| format!("{}{}{}", prefix, item, rest))
+        })
+        .collect()
+}
+
+/// Words of a line, as split by a shell, stopping at a comment.
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => word.push(c),
+            None if c == '\'' || c == '"' => {
+                quote = Some(c);
+                in_word = true;
+            }
+            None if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            None if c == '#' && !in_word => break,
+            None => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ini_profiles(text: &str) -> Vec<Profile> {
+        parse_ini(text).profiles(Path::new("/etc/ansible/hosts"))
+    }
+
+    fn profile<'a>(profiles: &'a [Profile], name: &str) -> &'a Profile {
+        profiles.iter().find(|p| p.name == name).unwrap()
+    }
+
+    #[test]
+    fn closer_variables_take_precedence() {
+        let profiles = ini_profiles(
+            "[all:vars]\n\
+             ansible_user=everyone\n\
+             ansible_port=2200\n\
+             ansible_ssh_private_key_file=~/.ssh/id_all\n\
+             \n\
+             [web]\n\
+             web1 ansible_user=alice\n\
+             web2\n\
+             \n\
+             [web:vars]\n\
+             ansible_user=webadmin\n\
+             \n\
+             [prod:children]\n\
+             web\n\
+             \n\
+             [prod:vars]\n\
+             ansible_user=produser\n\
+             ansible_port=2300\n",
+        );
+
+        let web1 = profile(&profiles, "web1");
+        assert_eq!(web1.user.as_deref(), Some("alice"));
+        assert_eq!(web1.port, 2300);
+        assert_eq!(web1.auth, AuthKind::IdentityFile);
+        assert_eq!(web1.identity_file, Some(PathBuf::from("~/.ssh/id_all")));
+
+        let web2 = profile(&profiles, "web2");
+        assert_eq!(web2.user.as_deref(), Some("webadmin"));
+    }
+
+    #[test]
+    fn children_give_the_group_and_tags() {
+        let profiles = ini_profiles(
+            "loose\n\
+             [db]\n\
+             db1\n\
+             [eu:children]\n\
+             db\n\
+             [prod:children]\n\
+             eu\n",
+        );
+
+        let db1 = profile(&profiles, "db1");
+        assert_eq!(db1.id, "ansible:/etc/ansible/hosts:db1");
+        assert_eq!(db1.group.as_deref(), Some("db"));
+        assert_eq!(db1.tags, ["prod", "eu"]);
+
+        let loose = profile(&profiles, "loose");
+        assert_eq!(loose.group, None);
+        assert!(loose.tags.is_empty());
+    }
+
+    #[test]
+    fn host_and_port() {
+        let profiles = ini_profiles(
+            "db.example.com:2222\n\
+             web.example.com:2222 ansible_port=2223\n\
+             ::1\n\
+             alias ansible_host=10.0.0.1 ansible_ssh_port=2224\n",
+        );
+
+        let db = profile(&profiles, "db.example.com");
+        assert_eq!(db.host, "db.example.com");
+        assert_eq!(db.port, 2222);
+        assert_eq!(profile(&profiles, "web.example.com").port, 2223);
+        assert_eq!(profile(&profiles, "::1").port, 22);
+
+        let alias = profile(&profiles, "alias");
+        assert_eq!(alias.host, "10.0.0.1");
+        assert_eq!(alias.port, 2224);
+    }
+
+    #[test]
+    fn ranges_are_expanded() {
+        assert_eq!(
+            expand("web[01:03].example.com"),
+            [
+                "web01.example.com",
+                "web02.example.com",
+                "web03.example.com"
+            ]
+        );
+        assert_eq!(expand("db-[a:c]"), ["db-a", "db-b", "db-c"]);
+        assert_eq!(expand("n[0:10:5]"), ["n0", "n5", "n10"]);
+        assert_eq!(expand("r[1:2]-[a:b]"), ["r1-a", "r1-b", "r2-a", "r2-b"]);
+        assert_eq!(expand("plain"), ["plain"]);
+        assert_eq!(expand("odd[x]"), ["odd[x]"]);
+    }
+
+    #[test]
+    fn huge_ranges_are_skipped() {
+        assert!(expand("web[0:99999999]").is_empty());
+        assert!(expand("web[0:99:1]-[0:99]").is_empty());
+        assert!(expand("web[0:99999999:100000]").len() <= MAX_EXPANDED_HOSTS);
+
+        let profiles = ini_profiles("[web]\nweb[0:99999999]\nweb-ok\n");
+        assert_eq!(profiles.len(), 1);
+    }
+
+    #[test]
+    fn only_ssh_connections_are_listed() {
+        let profiles = ini_profiles(
+            "localhost ansible_connection=local\n\
+             router ansible_connection=network_cli\n\
+             web ansible_connection=ssh\n\
+             old ansible_connection=paramiko\n\
+             [windows]\n\
+             win1\n\
+             [windows:vars]\n\
+             ansible_connection=winrm\n",
+        );
+
+        let names: Vec<&str> = profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["web", "old"]);
+    }
+
+    #[test]
+    fn proxy_jump_comes_from_ssh_arguments() {
+        assert_eq!(proxy_jump("-J bastion"), ["bastion"]);
+        assert_eq!(proxy_jump("-Jbob@a:2200,b"), ["bob@a:2200", "b"]);
+        assert_eq!(
+            proxy_jump("-o StrictHostKeyChecking=no -o ProxyJump=bastion"),
+            ["bastion"]
+        );
+        assert!(proxy_jump("-o ProxyJump=none").is_empty());
+        assert!(proxy_jump("-o ProxyCommand=\"ssh -W %h:%p bastion\"").is_empty());
+
+        let profiles = ini_profiles(
+            "web1\n\
+             web2 ansible_ssh_extra_args=\"-J other\"\n\
+             [all:vars]\n\
+             ansible_ssh_common_args='-o ProxyJump=bob@bastion:2200'\n",
+        );
+        assert_eq!(profile(&profiles, "web1").jump_hosts, ["bob@bastion:2200"]);
+        // The common arguments are used first, as both are given to ssh.
+        assert_eq!(profile(&profiles, "web2").jump_hosts, ["bob@bastion:2200"]);
+    }
+
+    #[test]
+    fn yaml_inventory() {
+        let inventory = parse_yaml(
+            "all:\n\
+             \x20 vars:\n\
+             \x20   ansible_user: everyone\n\
+             \x20 hosts:\n\
+             \x20   loose:\n\
+             \x20 children:\n\
+             \x20   web:\n\
+             \x20     hosts:\n\
+             \x20       web[1:2]:\n\
+             \x20         ansible_port: 2222\n\
+             \x20     vars:\n\
+             \x20       ansible_user: webadmin\n\
+             \x20     children:\n\
+             \x20       canary:\n\
+             \x20         hosts:\n\
+             \x20           web1:\n\
+             \x20             ansible_host: 10.0.0.1\n",
+        )
+        .unwrap();
+        let profiles = inventory.profiles(Path::new("inventory.yml"));
+
+        assert_eq!(
+            profile(&profiles, "loose").user.as_deref(),
+            Some("everyone")
+        );
+
+        let web1 = profile(&profiles, "web1");
+        assert_eq!(web1.host, "10.0.0.1");
+        assert_eq!(web1.port, 2222);
+        assert_eq!(web1.user.as_deref(), Some("webadmin"));
+        assert_eq!(web1.group.as_deref(), Some("canary"));
+        assert_eq!(web1.tags, ["web"]);
+
+        assert_eq!(profile(&profiles, "web2").group.as_deref(), Some("web"));
+    }
+
+    #[test]
+    fn group_vars_and_host_vars_directories() {
+        let dir = std::env::temp_dir().join(format!("flatline-ansible-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("group_vars").join("web")).unwrap();
+        std::fs::create_dir_all(dir.join("host_vars")).unwrap();
+        std::fs::write(
+            dir.join("hosts"),
+            "[web]\nweb1\nweb2 ansible_user=inline\n[web:vars]\nansible_port=2200\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("group_vars").join("all.yml"),
+            "ansible_user: everyone\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("group_vars").join("web").join("ssh.yml"),
+            "ansible_port: 2300\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("host_vars").join("web2"), "ansible_user: alice\n").unwrap();
+
+        let profiles = read(&dir.join("hosts")).unwrap();
+        let mut watched = vars_dirs(&dir.join("hosts"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        watched.sort();
+        assert_eq!(
+            watched,
+            [
+                dir.join("group_vars"),
+                dir.join("group_vars").join("web"),
+                dir.join("host_vars"),
+            ]
+        );
+
+        let web1 = profile(&profiles, "web1");
+        assert_eq!(web1.user.as_deref(), Some("everyone"));
+        assert_eq!(web1.port, 2300);
+        assert_eq!(profile(&profiles, "web2").user.as_deref(), Some("alice"));
+    }
+}
@@ -0,0 +1,176 @@
+use std::{
+    cell::RefCell,
+    path::PathBuf,
+    sync::OnceLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use glib::{prelude::*, subclass::prelude::*, subclass::Signal};
+use serde::{Deserialize, Serialize};
+use tracing::{error, trace, warn};
+
+use super::store::write_atomically;
+
+/// Version of the history file written by this build.
+const VERSION: u32 = 1;
+
+/// Most entries kept, the oldest ones are forgotten first.
+const MAX_ENTRIES: usize = 50;
+
+/// Connection made by the user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Destination as typed, or as `[user@]host[:port]` for a profile.
+    pub destination: String,
+    /// Id of the profile connected with, `None` for a quick connection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub last_used: i64,
+}
+
+impl HistoryEntry {
+    fn same_target(&self, other: &Self) -> bool {
+        match (&self.profile, &other.profile) {
+            (Some(id), Some(other_id)) => id == other_id,
+            (None, None) => self.destination == other.destination,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryFile {
+    version: u32,
+    #[serde(default)]
+    entries: Vec<HistoryEntry>,
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct History {
+        pub(super) path: RefCell<PathBuf>,
+
+        /// Most recent first.
+        pub(super) entries: RefCell<Vec<HistoryEntry>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for History {
+        const NAME: &'static str = "FlatLineHistory";
+        type Type = super::History;
+    }
+
+    impl ObjectImpl for History {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+            SIGNALS.get_or_init(|| vec![Signal::builder("changed").build()])
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Connections recently made, stored next to the application data.
+    pub struct History(ObjectSubclass<imp::History>);
+}
+
+impl History {
+    /// Default location of the history, under `$XDG_DATA_HOME/flatline`.
+    pub fn default_path() -> PathBuf {
+        glib::user_data_dir().join("flatline").join("history.toml")
+    }
+
+    /// Load the history stored at `path`, an unreadable history is started over.
+    pub fn load(path: PathBuf) -> Self {
+        let history: Self = glib::Object::new();
+
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(text) => match toml::from_str::<HistoryFile>(&text) {
+                Ok(file) if file.version <= VERSION => file.entries,
+                Ok(file) => {
+                    warn!("unsupported history version {}", file.version);
+                    Vec::new()
+                }
+                Err(e) => {
+                    error!("failed to read {} : {}", path.display(), e);
+                    Vec::new()
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                trace!("no history at {}", path.display());
+                Vec::new()
+            }
+            Err(e) => {
+                error!("failed to read {} : {}", path.display(), e);
+                Vec::new()
+            }
+        };
+
+        let imp = history.imp();
+        imp.path.replace(path);
+        imp.entries.replace(entries);
+        history
+    }
+
+    /// Entries, most recent first.
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        self.imp().entries.borrow().clone()
+    }
+
+    /// Record a connection to `destination`, made with the profile `profile` if any.
+    pub fn record(&self, destination: &str, profile: Option<&str>) {
+        let last_used = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs() as i64)
+            .unwrap_or_default();
+        let entry = HistoryEntry {
+            destination: destination.to_owned(),
+            profile: profile.map(str::to_owned),
+            last_used,
+        };
+
+        {
+            let mut entries = self.imp().entries.borrow_mut();
+            entries.retain(|e| !e.same_target(&entry));
+            entries.insert(0, entry);
+            entries.truncate(MAX_ENTRIES);
+        }
+
+        self.changed();
+    }
+
+    pub fn clear(&self) {
+        self.imp().entries.borrow_mut().clear();
+        self.changed();
+    }
+
+    fn changed(&self) {
+        self.emit_by_name::<()>("changed", &[]);
+
+        let imp = self.imp();
+        let path = imp.path.borrow().clone();
+        let file = HistoryFile {
+            version: VERSION,
+            entries: imp.entries.borrow().clone(),
+        };
+
+        let res = toml::to_string_pretty(&file)
+            .map_err(std::io::Error::other)
+            .and_then(|text| write_atomically(&path, &text));
+        if let Err(e) = res {
+            error!("failed to write {} : {}", path.display(), e);
+        }
+    }
+
+    pub fn connect_changed<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_local("changed", false, move |values| {
+            match values[0].get::<Self>() {
+                Ok(history) => f(&history),
+                Err(e) => warn!("unexpected changed signal : {}", e),
+            }
+            None
+        })
+    }
+}
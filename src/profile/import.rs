@@ -0,0 +1,328 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use flatline_session::{percent_decode, ConnectOptions, DEFAULT_PORT};
+use tracing::{trace, warn};
+
+use super::{ssh_config, AuthKind, Profile};
+
+/// Client whose saved connections can be imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+    SshConfig,
+    Remmina,
+    Putty,
+}
+
+impl ImportSource {
+    pub const ALL: [Self; 3] = [Self::SshConfig, Self::Remmina, Self::Putty];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::SshConfig => "OpenSSH",
+            Self::Remmina => "Remmina",
+            Self::Putty => "PuTTY",
+        }
+    }
+
+    /// File or directory the connections are read from.
+    pub fn location(self) -> PathBuf {
+        match self {
+            Self::SshConfig => ssh_config::default_path(),
+            Self::Remmina => glib::user_data_dir().join("remmina"),
+            Self::Putty => glib::home_dir().join(".putty").join("sessions"),
+        }
+    }
+
+    /// Read the connections of this source, as new profiles.
+    pub fn read(self) -> anyhow::Result<Vec<Profile>> {
+        let location = self.location();
+        let profiles = match self {
+            Self::SshConfig => ssh_config::read(&location)
+                .with_context(|| format!("failed to read {}", location.display()))?
+                .iter()
+                .map(ssh_config::SshHost::profile)
+                .collect(),
+            Self::Remmina => read_dir(&location, "remmina", read_remmina)?,
+            Self::Putty => read_dir(&location, "", read_putty)?,
+        };
+        Ok(profiles)
+    }
+}
+
+/// Read every file of `dir` with the extension `extension`, any if it is empty. Files that
+/// aren't ssh connections are skipped.
+fn read_dir(
+    dir: &Path,
+    extension: &str,
+    read: fn(&Path) -> anyhow::Result<Option<Profile>>,
+) -> anyhow::Result<Vec<Profile>> {
+    let entries =
+        std::fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))?;
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| extension.is_empty() || path.extension().is_some_and(|e| e == extension))
+        .collect();
+    paths.sort();
+
+    let mut profiles = Vec::new();
+    for path in paths {
+        match read(&path) {
+            Ok(Some(profile)) => profiles.push(profile),
+            Ok(None) => trace!("{} is not an ssh connection", path.display()),
+            Err(e) => warn!("failed to import {} : {:#}", path.display(), e),
+        }
+    }
+    Ok(profiles)
+}
+
+/// Read a Remmina connection file, `None` if it isn't an ssh connection.
+fn read_remmina(path: &Path) -> anyhow::Result<Option<Profile>> {
+    parse_remmina(&std::fs::read_to_string(path)?)
+}
+
+fn parse_remmina(text: &str) -> anyhow::Result<Option<Profile>> {
+    const GROUP: &str = "remmina";
+
+    let file = glib::KeyFile::new();
+    file.load_from_data(text, glib::KeyFileFlags::NONE)?;
+    let string = |key: &str| {
+        file.string(GROUP, key)
+            .ok()
+            .map(|value| value.trim().to_owned())
+            .filter(|value| !value.is_empty())
+    };
+
+    if !string("protocol").is_some_and(|protocol| protocol.eq_ignore_ascii_case("ssh")) {
+        return Ok(None);
+    }
+
+    let server = string("server").context("the connection has no server")?;
+    let server: ConnectOptions = server.parse()?;
+
+    let mut profile = Profile {
+        name: string("name").unwrap_or_default(),
+        group: string("group"),
+        host: server.host,
+        port: server.port,
+        user: string("username").or_else(|| string("ssh_username")),
+        ..Profile::default()
+    };
+
+    // Values of the ssh plugin authentication combo.
+    profile.auth = match file.integer(GROUP, "ssh_auth").unwrap_or(-1) {
+        0 => AuthKind::Password,
+        1 => AuthKind::IdentityFile,
+        2 => AuthKind::Agent,
+        _ => AuthKind::Auto,
+    };
+    if profile.auth == AuthKind::IdentityFile {
+        profile.identity_file = string("ssh_privatekey").map(PathBuf::from);
+        if profile.identity_file.is_none() {
+            profile.auth = AuthKind::Auto;
+        }
+    }
+
+    if file.integer(GROUP, "ssh_tunnel_enabled").unwrap_or(0) == 1 {
+        if let Some(tunnel) = string("ssh_tunnel_server") {
+            let jump = match string("ssh_tunnel_username") {
+                Some(user) => format!("{}@{}", user, tunnel),
+                None => tunnel,
+            };
+            profile.jump_hosts.push(jump);
+        }
+    }
+
+    Ok(Some(profile))
+}
+
+/// Read a PuTTY session file, `None` if it isn't an ssh session.
+fn read_putty(path: &Path) -> anyhow::Result<Option<Profile>> {
+    // Session names are the file names, with PuTTY's `%XX` escapes.
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let name = percent_decode(&name).unwrap_or_else(|| name.into_owned());
+
+    parse_putty(name, &std::fs::read_to_string(path)?)
+}
+
+fn parse_putty(name: String, text: &str) -> anyhow::Result<Option<Profile>> {
+    let value = |key: &str| {
+        text.lines()
+            .filter_map(|line| line.split_once('='))
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value.trim().to_owned())
+            .filter(|value| !value.is_empty())
+    };
+
+    if value("Protocol").is_some_and(|protocol| protocol != "ssh") {
+        return Ok(None);
+    }
+    let Some(host) = value("HostName") else {
+        // The default settings and sessions without a host.
+        return Ok(None);
+    };
+
+    // The host name may carry the user, as in `user@host`.
+    let (user, host) = match host.rsplit_once('@') {
+        Some((user, host)) => (Some(user.to_owned()), host.to_owned()),
+        None => (value("UserName"), host),
+    };
+
+    let mut profile = Profile {
+        name,
+        host,
+        port: value("PortNumber")
+            .and_then(|port| port.parse().ok())
+            .unwrap_or(DEFAULT_PORT),
+        user,
+        ..Profile::default()
+    };
+
+    // PuTTY's own key format can't be loaded, the agent may still hold the key.
+    match value("PublicKeyFile") {
+        Some(key) if !key.ends_with(".ppk") => {
+            profile.auth = AuthKind::IdentityFile;
+            profile.identity_file = Some(PathBuf::from(key));
+        }
+        Some(key) => trace!("skipping PuTTY key {}", key),
+        None => {}
+    }
+
+    // Proxy method 6 connects through another ssh server.
+    if value("ProxyMethod").as_deref() == Some("6") {
+        if let Some(proxy) = value("ProxyHost") {
+            let mut jump = proxy;
+            if let Some(user) = value("ProxyUsername") {
+                jump = format!("{}@{}", user, jump);
+            }
+            if let Some(port) = value("ProxyPort").filter(|port| port != "22") {
+                jump = format!("{}:{}", jump, port);
+            }
+            profile.jump_hosts.push(jump);
+        }
+    }
+
+    Ok(Some(profile))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remmina_ssh_connection() {
+        let profile = parse_remmina(
+            "[remmina]\n\
+             name=Web\n\
+             group=Production\n\
+             protocol=SSH\n\
+             server=web.example.com:2222\n\
+             username=alice\n\
+             ssh_auth=1\n\
+             ssh_privatekey=/home/alice/.ssh/id_ed25519\n\
+             ssh_tunnel_enabled=1\n\
+             ssh_tunnel_server=bastion.example.com:2200\n\
+             ssh_tunnel_username=bob\n",
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(profile.name, "Web");
+        assert_eq!(profile.group.as_deref(), Some("Production"));
+        assert_eq!(profile.host, "web.example.com");
+        assert_eq!(profile.port, 2222);
+        assert_eq!(profile.user.as_deref(), Some("alice"));
+        assert_eq!(profile.auth, AuthKind::IdentityFile);
+        assert_eq!(
+            profile.identity_file,
+            Some(PathBuf::from("/home/alice/.ssh/id_ed25519"))
+        );
+        assert_eq!(profile.jump_hosts, ["bob@bastion.example.com:2200"]);
+    }
+
+    #[test]
+    fn remmina_auth_methods() {
+        let auth = |ssh_auth: &str| {
+            parse_remmina(&format!(
+                "[remmina]\nprotocol=SSH\nserver=web\nssh_auth={}\n",
+                ssh_auth
+            ))
+            .unwrap()
+            .unwrap()
+            .auth
+        };
+
+        assert_eq!(auth("0"), AuthKind::Password);
+        assert_eq!(auth("2"), AuthKind::Agent);
+        // An identity file without a key falls back to the automatic method.
+        assert_eq!(auth("1"), AuthKind::Auto);
+        assert_eq!(auth("5"), AuthKind::Auto);
+    }
+
+    #[test]
+    fn remmina_other_protocols_are_skipped() {
+        let profile = parse_remmina("[remmina]\nprotocol=RDP\nserver=desktop\n").unwrap();
+        assert_eq!(profile, None);
+
+        assert!(parse_remmina("[remmina]\nprotocol=SSH\n").is_err());
+    }
+
+    #[test]
+    fn putty_ssh_session() {
+        let profile = parse_putty(
+            "Web 01".to_owned(),
+            "HostName=alice@web.example.com\n\
+             Protocol=ssh\n\
+             PortNumber=2222\n\
+             UserName=ignored\n\
+             PublicKeyFile=/home/alice/.ssh/id_rsa\n\
+             ProxyMethod=6\n\
+             ProxyHost=bastion.example.com\n\
+             ProxyPort=2200\n\
+             ProxyUsername=bob\n",
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(profile.name, "Web 01");
+        assert_eq!(profile.host, "web.example.com");
+        assert_eq!(profile.port, 2222);
+        assert_eq!(profile.user.as_deref(), Some("alice"));
+        assert_eq!(profile.auth, AuthKind::IdentityFile);
+        assert_eq!(
+            profile.identity_file,
+            Some(PathBuf::from("/home/alice/.ssh/id_rsa"))
+        );
+        assert_eq!(profile.jump_hosts, ["bob@bastion.example.com:2200"]);
+    }
+
+    #[test]
+    fn putty_defaults_and_ppk_keys() {
+        let profile = parse_putty(
+            "db".to_owned(),
+            "HostName=db\nUserName=carol\nPublicKeyFile=C:\\keys\\db.ppk\nProxyMethod=0\n",
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(profile.port, DEFAULT_PORT);
+        assert_eq!(profile.user.as_deref(), Some("carol"));
+        assert_eq!(profile.auth, AuthKind::Auto);
+        assert_eq!(profile.identity_file, None);
+        assert!(profile.jump_hosts.is_empty());
+    }
+
+    #[test]
+    fn putty_sessions_without_ssh_host_are_skipped() {
+        let telnet = parse_putty("t".to_owned(), "HostName=router\nProtocol=telnet\n").unwrap();
+        assert_eq!(telnet, None);
+
+        let settings = parse_putty("Default%20Settings".to_owned(), "Protocol=ssh\n").unwrap();
+        assert_eq!(settings, None);
+    }
+}
@@ -0,0 +1,324 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use flatline_session::{AuthMethod, ConnectOptions, DEFAULT_PORT};
+use serde::{Deserialize, Serialize};
+
+mod ansible;
+mod history;
+pub mod import;
+mod search;
+pub mod share;
+pub mod ssh_config;
+mod store;
+
+pub use history::{History, HistoryEntry};
+pub use ssh_config::SshHost;
+pub use store::{ImportMode, ProfileStore, SourceConfig, SourceKind};
+
+/// How a profile authenticates, see [`AuthMethod`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthKind {
+    #[default]
+    Auto,
+    Agent,
+    Password,
+    IdentityFile,
+}
+
+/// Terminal appearance of a profile, unset values keep the defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Appearance {
+    /// Pango font description, such as `Monospace 11`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font: Option<String>,
+    /// Colors are CSS colors, such as `#1e1e1e`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub foreground: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stderr_color: Option<String>,
+}
+
+/// Settings a profile inherits from its parent or its group when it doesn't set them.
+///
+/// Each setting comes from the first of, in order : the profile's own settings, its parent
+/// once resolved the same way (so with the parent's own parent and group), then the defaults of
+/// the profile's own group. The environment is merged in that order, as are the colors and
+/// font of the appearance. A jump host `none` stops inheriting jump hosts, and a proxy command
+/// `none` stops inheriting proxy commands.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Defaults {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// [`AuthKind::Auto`] is inherited too.
+    pub auth: AuthKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity_file: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub jump_hosts: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_command: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    pub appearance: Appearance,
+}
+
+impl Defaults {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Saved connection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    /// Stable identifier, kept when the profile is renamed.
+    pub id: String,
+    pub name: String,
+    /// Group the profile is listed under, `None` to list it at the top level.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Free-form labels, matched by the search.
+    pub tags: Vec<String>,
+    /// Id of the profile whose settings are inherited, see [`Defaults`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// Pinned at the top of the new-pane page.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub favourite: bool,
+    pub host: String,
+    pub port: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    pub auth: AuthKind,
+    /// Private key used with [`AuthKind::IdentityFile`], a leading `~` is the home directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity_file: Option<PathBuf>,
+    /// Hosts to go through, in order, as `[user@]host[:port]`.
+    pub jump_hosts: Vec<String>,
+    /// Command carrying the connection to the first host, see
+    /// [`ConnectOptions::proxy_command`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_command: Option<String>,
+    pub env: BTreeMap<String, String>,
+    pub appearance: Appearance,
+    /// Label of the read-only source the profile comes from, `None` for a saved profile.
+    #[serde(skip)]
+    pub source: Option<String>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            id: glib::uuid_string_random().to_string(),
+            name: String::new(),
+            group: None,
+            tags: Vec::new(),
+            parent: None,
+            favourite: false,
+            host: String::new(),
+            port: DEFAULT_PORT,
+            user: None,
+            auth: AuthKind::default(),
+            identity_file: None,
+            jump_hosts: Vec::new(),
+            proxy_command: None,
+            env: BTreeMap::new(),
+            appearance: Appearance::default(),
+            source: None,
+        }
+    }
+}
+
+impl Profile {
+    /// Unsaved profile for a destination typed by the user, as `[user@]host[:port]` or an
+    /// `ssh://` URI.
+    ///
+    /// A host matching an alias of `ssh_hosts` is resolved like `ssh` would, the user typed and
+    /// a port other than 22 take precedence over the configured ones.
+    pub fn from_destination(destination: &str, ssh_hosts: &[SshHost]) -> anyhow::Result<Self> {
+        let options: ConnectOptions = destination.parse()?;
+
+        let mut profile = match ssh_hosts.iter().find(|host| host.alias == options.host) {
+            Some(host) => host.profile(),
+            None => Self {
+                host: options.host.clone(),
+                ..Self::default()
+            },
+        };
+        profile.name = String::new();
+
+        if options.user.is_some() {
+            profile.user = options.user;
+        }
+        if options.port != DEFAULT_PORT {
+            profile.port = options.port;
+        }
+
+        Ok(profile)
+    }
+
+    /// Name shown to the user, the destination if the profile isn't named.
+    pub fn display_name(&self) -> String {
+        if self.name.is_empty() {
+            self.destination()
+        } else {
+            self.name.clone()
+        }
+    }
+
+    /// Destination as `[user@]host[:port]`.
+    pub fn destination(&self) -> String {
+        ConnectOptions {
+            user: self.user.clone(),
+            ..ConnectOptions::new(self.host.clone(), self.port)
+        }
+        .to_string()
+    }
+
+    /// Options to connect with this profile.
+    pub fn connect_options(&self) -> anyhow::Result<ConnectOptions> {
+        let auth = match self.auth {
+            AuthKind::Auto => AuthMethod::Auto,
+            AuthKind::Agent => AuthMethod::Agent,
+            AuthKind::Password => AuthMethod::Password,
+            AuthKind::IdentityFile => {
+                let path = self
+                    .identity_file
+                    .as_deref()
+                    .context("no identity file is set")?;
+                AuthMethod::IdentityFile(expand_home(path))
+            }
+        };
+
+        let jump_hosts = self
+            .jump_hosts
+            .iter()
+            .filter(|jump| *jump != "none")
+            .map(|jump| jump.parse())
+            .collect::<Result<_, _>>()?;
+
+        Ok(ConnectOptions {
+            user: self.user.clone(),
+            auth,
+            jump_hosts,
+            proxy_command: self
+                .proxy_command
+                .clone()
+                .filter(|command| command != "none"),
+            ..ConnectOptions::new(self.host.clone(), self.port)
+        })
+    }
+
+    /// How well the profile matches a search, higher is better, `None` if it doesn't match.
+    ///
+    /// Each word of the query must fuzzily match the name, host, user, group or a tag.
+    pub fn search_score(&self, query: &str) -> Option<i32> {
+        let fields: Vec<&str> = [
+            Some(self.name.as_str()),
+            Some(self.host.as_str()),
+            self.user.as_deref(),
+            self.group.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(self.tags.iter().map(String::as_str))
+        .collect();
+
+        query.split_whitespace().try_fold(0, |total, word| {
+            let best = fields
+                .iter()
+                .filter_map(|field| search::fuzzy_score(word, field))
+                .max()?;
+            Some(total + best)
+        })
+    }
+
+    /// Settings of the profile other profiles inherit.
+    pub fn defaults(&self) -> Defaults {
+        Defaults {
+            user: self.user.clone(),
+            auth: self.auth,
+            identity_file: self.identity_file.clone(),
+            jump_hosts: self.jump_hosts.clone(),
+            proxy_command: self.proxy_command.clone(),
+            env: self.env.clone(),
+            appearance: self.appearance.clone(),
+        }
+    }
+
+    /// Profile without its own inheritable settings, inheriting all of them.
+    pub fn without_defaults(&self) -> Self {
+        Self {
+            user: None,
+            auth: AuthKind::Auto,
+            identity_file: None,
+            jump_hosts: Vec::new(),
+            proxy_command: None,
+            env: BTreeMap::new(),
+            appearance: Appearance::default(),
+            ..self.clone()
+        }
+    }
+
+    /// Fill the settings the profile doesn't set with `defaults`.
+    pub fn inherit(&mut self, defaults: &Defaults) {
+        if self.user.is_none() {
+            self.user = defaults.user.clone();
+        }
+        if self.auth == AuthKind::Auto {
+            self.auth = defaults.auth;
+            self.identity_file = defaults.identity_file.clone();
+        }
+        if self.jump_hosts.is_empty() {
+            self.jump_hosts = defaults.jump_hosts.clone();
+        }
+        if self.proxy_command.is_none() {
+            self.proxy_command = defaults.proxy_command.clone();
+        }
+        for (name, value) in &defaults.env {
+            self.env
+                .entry(name.clone())
+                .or_insert_with(|| value.clone());
+        }
+
+        let appearance = &mut self.appearance;
+        for (value, default) in [
+            (&mut appearance.font, &defaults.appearance.font),
+            (&mut appearance.foreground, &defaults.appearance.foreground),
+            (&mut appearance.background, &defaults.appearance.background),
+            (
+                &mut appearance.stderr_color,
+                &defaults.appearance.stderr_color,
+            ),
+        ] {
+            if value.is_none() {
+                *value = default.clone();
+            }
+        }
+    }
+
+    pub fn env(&self) -> Vec<(String, String)> {
+        self.env
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+}
+
+/// Replace a leading `~` with the home directory.
+pub fn expand_home(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => glib::home_dir().join(rest),
+        Err(_) => path.to_owned(),
+    }
+}
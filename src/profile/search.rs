@@ -0,0 +1,71 @@
+/// Score of `text` for the fuzzy `query`, higher is better, `None` if it doesn't match.
+///
+/// Every character of the query must appear in the text in order, ignoring case. Consecutive
+/// characters and characters starting a word score more, gaps score less.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+
+    let mut score = 0;
+    let mut start = 0;
+    let mut previous: Option<usize> = None;
+
+    for c in query.chars().flat_map(char::to_lowercase) {
+        let found = start + text[start..].iter().position(|t| *t == c)?;
+
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (found - start).min(3) as i32;
+
+        previous = Some(found);
+        start = found + 1;
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn characters_must_appear_in_order() {
+        assert!(fuzzy_score("web", "web-01").is_some());
+        assert_eq!(fuzzy_score("bew", "web-01"), None);
+        assert_eq!(fuzzy_score("xyz", "web-01"), None);
+        assert_eq!(fuzzy_score("web-010", "web-01"), None);
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(fuzzy_score("", "web-01"), Some(0));
+        assert_eq!(fuzzy_score("", ""), Some(0));
+    }
+
+    #[test]
+    fn case_is_ignored() {
+        assert_eq!(fuzzy_score("WEB", "web-01"), fuzzy_score("web", "web-01"));
+        assert_eq!(fuzzy_score("web", "WEB-01"), fuzzy_score("web", "web-01"));
+    }
+
+    #[test]
+    fn consecutive_characters_score_more() {
+        assert!(fuzzy_score("web", "web") > fuzzy_score("web", "w-e-b"));
+        assert!(fuzzy_score("prod", "prod-db") > fuzzy_score("prod", "p-r-o-d"));
+    }
+
+    #[test]
+    fn word_starts_score_more() {
+        assert!(fuzzy_score("db", "a-db") > fuzzy_score("db", "adbx"));
+        assert!(fuzzy_score("d", "prod-db") < fuzzy_score("d", "db"));
+    }
+
+    #[test]
+    fn gaps_score_less() {
+        assert!(fuzzy_score("wb", "w-b") > fuzzy_score("wb", "wxxxb"));
+    }
+}
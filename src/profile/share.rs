@@ -0,0 +1,169 @@
+//! Profile files shared between users.
+//!
+//! A shared file is TOML, or JSON when its extension is `.json`, holding the version of the
+//! format and a list of profiles:
+//!
+//! ```toml
+//! version = 1
+//!
+//! [[profiles]]
+//! id = "6f1c2d7e-3b4a-4f8e-9d0c-1a2b3c4d5e6f"
+//! name = "Database"
+//! group = "Production"
+//! tags = ["db", "eu"]
+//! host = "db1.example.com"
+//! port = 22
+//! user = "alice"
+//! # auto, agent, password or identity-file
+//! auth = "identity-file"
+//! identity_file = "~/.ssh/id_team"
+//! jump_hosts = ["bastion.example.com"]
+//! env = { LANG = "C.UTF-8" }
+//! appearance = { font = "Monospace 11", background = "#1e1e1e" }
+//! ```
+//!
+//! Only `host` is required. The `id` keeps a profile identified when the file is imported
+//! again, profiles without one are identified by the file and their destination.
+//!
+//! Profiles are exported with the settings they inherit. Passwords are never stored, and
+//! environment variables that look like secrets are left out.
+
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+
+use super::{store::write_atomically, Profile};
+
+/// Version of the shared format written by this build.
+const VERSION: u32 = 1;
+
+/// Words of environment variable names, separated by `_`, that hint at a secret.
+const SECRET_HINTS: [&str; 5] = ["PASSWORD", "PASSWD", "SECRET", "TOKEN", "KEY"];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SharedFile<P> {
+    version: u32,
+    #[serde(default)]
+    profiles: Vec<P>,
+}
+
+/// Profile as read from a shared file, where the id may be missing.
+#[derive(Debug, Deserialize)]
+struct SharedProfile {
+    id: Option<String>,
+    #[serde(flatten)]
+    profile: Profile,
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+/// Write `profiles` to `path`, without their secrets nor personal settings.
+pub fn export(profiles: &[Profile], path: &Path) -> anyhow::Result<()> {
+    let file = SharedFile::<Profile> {
+        version: VERSION,
+        profiles: profiles.iter().map(shareable).collect(),
+    };
+
+    let text = if is_json(path) {
+        serde_json::to_string_pretty(&file)?
+    } else {
+        toml::to_string_pretty(&file)?
+    };
+    write_atomically(path, &text).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Read the profiles shared in `path`.
+pub fn read(path: &Path) -> anyhow::Result<Vec<Profile>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+
+    let file: SharedFile<SharedProfile> = if is_json(path) {
+        serde_json::from_str(&text)?
+    } else {
+        toml::from_str(&text)?
+    };
+    if file.version > VERSION {
+        bail!(
+            "{} was written by a newer version of Flatline (version {})",
+            path.display(),
+            file.version
+        );
+    }
+
+    let mut profiles: Vec<Profile> = Vec::with_capacity(file.profiles.len());
+    for (index, shared) in file.profiles.into_iter().enumerate() {
+        let mut profile = shared.profile;
+        if profile.host.trim().is_empty() {
+            bail!(
+                "connection {} of {} has no host",
+                display_name(&profile, index),
+                path.display()
+            );
+        }
+
+        profile.id = match shared.id {
+            Some(id) => id,
+            // Destinations listed more than once are told apart by their rank.
+            None => {
+                let id = format!("shared:{}:{}", path.display(), profile.destination());
+                let rank = profiles
+                    .iter()
+                    .filter(|p| p.id == id || p.id.starts_with(&format!("{}#", id)))
+                    .count();
+                match rank {
+                    0 => id,
+                    rank => format!("{}#{}", id, rank + 1),
+                }
+            }
+        };
+        profiles.push(profile);
+    }
+
+    Ok(profiles)
+}
+
+fn display_name(profile: &Profile, index: usize) -> String {
+    if profile.name.is_empty() {
+        format!("#{}", index + 1)
+    } else {
+        format!("\"{}\"", profile.name)
+    }
+}
+
+/// Whether the environment variable `name` looks like it holds a secret, such as `DB_PASSWORD`
+/// but not `KEYBOARD_LAYOUT`.
+fn is_secret(name: &str) -> bool {
+    name.split('_').any(|word| {
+        SECRET_HINTS
+            .iter()
+            .any(|hint| word.eq_ignore_ascii_case(hint))
+    })
+}
+
+fn shareable(profile: &Profile) -> Profile {
+    let mut profile = profile.clone();
+    profile.favourite = false;
+    profile.env.retain(|name, _| !is_secret(name));
+    profile
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_whole_words() {
+        assert!(is_secret("DB_PASSWORD"));
+        assert!(is_secret("api_key"));
+        assert!(is_secret("TOKEN"));
+        assert!(is_secret("AWS_SECRET_ACCESS_KEY"));
+        assert!(!is_secret("KEYBOARD_LAYOUT"));
+        assert!(!is_secret("MONKEY"));
+        assert!(!is_secret("TOKENIZER_MODE"));
+        assert!(!is_secret("LANG"));
+    }
+}
@@ -0,0 +1,483 @@
+use std::path::{Path, PathBuf};
+
+use flatline_session::{ConnectOptions, DEFAULT_PORT};
+use tracing::{trace, warn};
+
+use super::{AuthKind, Profile};
+
+/// Deepest chain of `Include` directives followed.
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// Longest chain of `ProxyJump` hosts resolved through the configuration.
+const MAX_JUMP_DEPTH: usize = 8;
+
+/// `Host` block of an OpenSSH client configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SshHost {
+    /// Name given on the `Host` line.
+    pub alias: String,
+    pub host_name: Option<String>,
+    pub port: Option<u16>,
+    pub user: Option<String>,
+    pub identity_file: Option<PathBuf>,
+    /// Hosts of `ProxyJump`, in order, with the aliases they name resolved to their
+    /// destinations and their own jump hosts.
+    pub proxy_jump: Vec<String>,
+    /// `ProxyCommand`, `none` included as it stops inheriting one.
+    pub proxy_command: Option<String>,
+}
+
+impl SshHost {
+    /// Profile connecting like `ssh <alias>` would.
+    pub fn profile(&self) -> Profile {
+        let mut profile = Profile {
+            name: self.alias.clone(),
+            host: self.host_name.clone().unwrap_or_else(|| self.alias.clone()),
+            user: self.user.clone(),
+            identity_file: self.identity_file.clone(),
+            jump_hosts: self.proxy_jump.clone(),
+            proxy_command: self.proxy_command.clone(),
+            ..Profile::default()
+        };
+        if let Some(port) = self.port {
+            profile.port = port;
+        }
+        if profile.identity_file.is_some() {
+            profile.auth = AuthKind::IdentityFile;
+        }
+        profile
+    }
+}
+
+/// Options of a `Host` block, or of the top of the file for the block matching every host.
+#[derive(Debug, Clone)]
+struct Block {
+    /// Patterns of the `Host` line, none for a `Match` block which is never applied.
+    patterns: Vec<String>,
+    /// Keyword in lowercase and value, in order.
+    options: Vec<(String, String)>,
+}
+
+impl Block {
+    fn new(patterns: Vec<String>) -> Self {
+        Self {
+            patterns,
+            options: Vec::new(),
+        }
+    }
+
+    /// Whether the block applies to `host` : one of the patterns matches it and none of the
+    /// negated ones does.
+    fn matches(&self, host: &str) -> bool {
+        let mut matched = false;
+        for pattern in &self.patterns {
+            match pattern.strip_prefix('!') {
+                Some(negated) if glob_matches(negated, host) => return false,
+                Some(_) => {}
+                None => matched |= glob_matches(pattern, host),
+            }
+        }
+        matched
+    }
+}
+
+/// Location of the user's OpenSSH client configuration.
+pub fn default_path() -> PathBuf {
+    glib::home_dir().join(".ssh").join("config")
+}
+
+/// Hosts of the user's configuration, none if it can't be read.
+pub fn user_hosts() -> Vec<SshHost> {
+    let path = default_path();
+    match read(&path) {
+        Ok(hosts) => hosts,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            trace!("no ssh config at {}", path.display());
+            Vec::new()
+        }
+        Err(e) => {
+            warn!("failed to read {} : {}", path.display(), e);
+            Vec::new()
+        }
+    }
+}
+
+/// Read the `Host` blocks of the configuration at `path`, following its `Include`s.
+///
+/// Only hosts that can be connected to by name are returned, patterns with wildcards or
+/// negations are not, but their options apply to the hosts they match as do the options at
+/// the top of the file. `Match` blocks are skipped.
+pub fn read(path: &Path) -> std::io::Result<Vec<SshHost>> {
+    let mut blocks = vec![Block::new(vec!["*".to_owned()])];
+    read_into(path, &mut blocks, 0)?;
+    Ok(hosts(&blocks))
+}
+
+fn read_into(path: &Path, blocks: &mut Vec<Block>, depth: usize) -> std::io::Result<()> {
+    let text = std::fs::read_to_string(path)?;
+    parse_into(&text, blocks, depth);
+    Ok(())
+}
+
+/// Add the blocks of `text` to `blocks`, its first options go to the last block.
+fn parse_into(text: &str, blocks: &mut Vec<Block>, depth: usize) {
+    for line in text.lines() {
+        let Some((keyword, value)) = split_line(line) else {
+            continue;
+        };
+
+        match keyword.to_ascii_lowercase().as_str() {
+            "host" => {
+                let patterns = value
+                    .split_whitespace()
+                    .map(|pattern| unquote(pattern).to_owned())
+                    .collect();
+                blocks.push(Block::new(patterns));
+            }
+            "match" => blocks.push(Block::new(Vec::new())),
+            "include" if depth < MAX_INCLUDE_DEPTH => {
+                for include in value.split_whitespace() {
+                    let include = unquote(include);
+                    if include.contains(['*', '?', '[']) {
+                        trace!("skipping include pattern {}", include);
+                        continue;
+                    }
+
+                    let include = super::expand_home(Path::new(include));
+                    // Relative includes are relative to ~/.ssh, as for the user configuration.
+                    let include = glib::home_dir().join(".ssh").join(include);
+
+                    // The block the include is in goes on after it.
+                    let count = blocks.len();
+                    if let Err(e) = read_into(&include, blocks, depth + 1) {
+                        warn!("failed to read {} : {}", include.display(), e);
+                    }
+                    if blocks.len() > count {
+                        blocks.push(Block::new(blocks[count - 1].patterns.clone()));
+                    }
+                }
+            }
+            keyword => {
+                if let Some(block) = blocks.last_mut() {
+                    block
+                        .options
+                        .push((keyword.to_owned(), unquote(value).to_owned()));
+                }
+            }
+        }
+    }
+}
+
+/// Hosts named by the `Host` lines of `blocks`, with the options of every block matching them.
+fn hosts(blocks: &[Block]) -> Vec<SshHost> {
+    let mut aliases: Vec<&str> = Vec::new();
+    for pattern in blocks.iter().flat_map(|block| &block.patterns) {
+        if !pattern.contains(['*', '?', '!']) && !aliases.contains(&pattern.as_str()) {
+            aliases.push(pattern);
+        }
+    }
+
+    aliases
+        .into_iter()
+        .map(|alias| {
+            let mut host = configure(blocks, alias);
+            let mut chain = vec![alias.to_owned()];
+            host.proxy_jump = resolve_jumps(blocks, &host.proxy_jump, &mut chain);
+            host
+        })
+        .collect()
+}
+
+/// Options of `alias`, the first value obtained for an option being the one used.
+fn configure(blocks: &[Block], alias: &str) -> SshHost {
+    let mut host = SshHost {
+        alias: alias.to_owned(),
+        ..SshHost::default()
+    };
+    // `ProxyJump none` is a value too, which stops later ones from applying.
+    let mut proxy_jump: Option<Vec<String>> = None;
+
+    let options = blocks
+        .iter()
+        .filter(|block| block.matches(alias))
+        .flat_map(|block| &block.options);
+    for (keyword, value) in options {
+        match keyword.as_str() {
+            "hostname" if host.host_name.is_none() => host.host_name = Some(value.clone()),
+            "port" if host.port.is_none() => host.port = value.parse().ok(),
+            "user" if host.user.is_none() => host.user = Some(value.clone()),
+            "identityfile" if host.identity_file.is_none() => {
+                host.identity_file = Some(PathBuf::from(value));
+            }
+            "proxycommand" if host.proxy_command.is_none() => {
+                host.proxy_command = Some(value.clone());
+            }
+            "proxyjump" if proxy_jump.is_none() => {
+                proxy_jump = Some(if value.eq_ignore_ascii_case("none") {
+                    Vec::new()
+                } else {
+                    value
+                        .split(',')
+                        .map(|jump| unquote(jump.trim()).to_owned())
+                        .filter(|jump| !jump.is_empty())
+                        .collect()
+                });
+            }
+            _ => {}
+        }
+    }
+
+    host.proxy_jump = proxy_jump.unwrap_or_default();
+    host
+}
+
+/// Replace the hosts of `jumps` by the destinations the configuration gives them, preceded by
+/// their own jump hosts. Hosts already in `chain` are dropped, as they would loop.
+fn resolve_jumps(blocks: &[Block], jumps: &[String], chain: &mut Vec<String>) -> Vec<String> {
+    let mut resolved = Vec::new();
+
+    for jump in jumps {
+        let Ok(destination) = jump.parse::<ConnectOptions>() else {
+            resolved.push(jump.clone());
+            continue;
+        };
+        if chain.contains(&destination.host) {
+            trace!("skipping looping jump host {}", jump);
+            continue;
+        }
+
+        let config = configure(blocks, &destination.host);
+        if chain.len() < MAX_JUMP_DEPTH {
+            chain.push(destination.host.clone());
+            resolved.extend(resolve_jumps(blocks, &config.proxy_jump, chain));
+            chain.pop();
+        }
+
+        // What the jump host says takes precedence over the configuration.
+        let port = match destination.port {
+            DEFAULT_PORT => config.port.unwrap_or(DEFAULT_PORT),
+            port => port,
+        };
+        let mut hop = ConnectOptions::new(config.host_name.unwrap_or(destination.host), port);
+        hop.user = destination.user.or(config.user);
+        resolved.push(hop.to_string());
+    }
+
+    resolved
+}
+
+/// Match `text` against an OpenSSH pattern, where `*` matches any characters and `?` one.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    // Position after the last `*`, and the text position it was matched up to.
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut t) = (0, 0);
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c.eq_ignore_ascii_case(&text[t]) => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((after, matched)) => {
+                    p = after;
+                    t = matched + 1;
+                    star = Some((after, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Split a line into its keyword and value, `None` for blank lines and comments.
+fn split_line(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let end = line.find(|c: char| c.is_whitespace() || c == '=')?;
+    let (keyword, rest) = line.split_at(end);
+    let value = rest.trim_start().strip_prefix('=').unwrap_or(rest).trim();
+
+    Some((keyword, value))
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Vec<SshHost> {
+        let mut blocks = vec![Block::new(vec!["*".to_owned()])];
+        parse_into(text, &mut blocks, 0);
+        hosts(&blocks)
+    }
+
+    fn host<'a>(hosts: &'a [SshHost], alias: &str) -> &'a SshHost {
+        hosts.iter().find(|host| host.alias == alias).unwrap()
+    }
+
+    #[test]
+    fn hosts_and_their_options() {
+        let hosts = parse(
+            "# comment\n\
+             Host web \"db\"\n\
+             \x20   HostName web.example.com\n\
+             \x20   Port=2222\n\
+             \x20   User alice\n\
+             \x20   IdentityFile ~/.ssh/id_web\n\
+             Host *.internal !skip.internal\n\
+             \x20   User bob\n",
+        );
+
+        let aliases: Vec<&str> = hosts.iter().map(|host| host.alias.as_str()).collect();
+        assert_eq!(aliases, ["web", "db"]);
+
+        let web = host(&hosts, "web");
+        assert_eq!(web.host_name.as_deref(), Some("web.example.com"));
+        assert_eq!(web.port, Some(2222));
+        assert_eq!(web.user.as_deref(), Some("alice"));
+        assert_eq!(web.identity_file, Some(PathBuf::from("~/.ssh/id_web")));
+        // Both aliases of the line share its options.
+        assert_eq!(host(&hosts, "db").port, Some(2222));
+
+        let profile = web.profile();
+        assert_eq!(profile.host, "web.example.com");
+        assert_eq!(profile.auth, AuthKind::IdentityFile);
+    }
+
+    #[test]
+    fn first_value_wins_across_wildcards_and_global_options() {
+        let hosts = parse(
+            "User global\n\
+             Port 2200\n\
+             Host web\n\
+             \x20   User alice\n\
+             Host db\n\
+             Host *\n\
+             \x20   User everyone\n\
+             \x20   IdentityFile ~/.ssh/id_all\n\
+             Host w*\n\
+             \x20   HostName ignored\n\
+             Host d? !dx\n\
+             \x20   HostName db.example.com\n",
+        );
+
+        let web = host(&hosts, "web");
+        assert_eq!(web.user.as_deref(), Some("global"));
+        assert_eq!(web.port, Some(2200));
+        assert_eq!(web.identity_file, Some(PathBuf::from("~/.ssh/id_all")));
+        assert_eq!(web.host_name.as_deref(), Some("ignored"));
+
+        let db = host(&hosts, "db");
+        assert_eq!(db.user.as_deref(), Some("global"));
+        assert_eq!(db.host_name.as_deref(), Some("db.example.com"));
+    }
+
+    #[test]
+    fn match_blocks_are_skipped() {
+        let hosts = parse(
+            "Host web\n\
+             Match user root\n\
+             \x20   Port 2222\n",
+        );
+        assert_eq!(host(&hosts, "web").port, None);
+    }
+
+    #[test]
+    fn proxy_jump_aliases_are_resolved() {
+        let hosts = parse(
+            "Host web\n\
+             \x20   ProxyJump bastion,carol@inner:2201\n\
+             Host bastion\n\
+             \x20   HostName bastion.example.com\n\
+             \x20   User bob\n\
+             \x20   Port 2200\n\
+             Host inner\n\
+             \x20   HostName inner.example.com\n\
+             \x20   ProxyJump outer.example.com\n\
+             Host direct\n\
+             \x20   ProxyJump none\n\
+             \x20   ProxyJump bastion\n",
+        );
+
+        assert_eq!(
+            host(&hosts, "web").proxy_jump,
+            [
+                "bob@bastion.example.com:2200",
+                "outer.example.com",
+                "carol@inner.example.com:2201",
+            ]
+        );
+        assert!(host(&hosts, "direct").proxy_jump.is_empty());
+    }
+
+    #[test]
+    fn looping_jump_hosts_are_dropped() {
+        let hosts = parse(
+            "Host a\n\
+             \x20   ProxyJump b\n\
+             Host b\n\
+             \x20   ProxyJump a\n\
+             Host bastion\n\
+             \x20   HostName bastion.example.com\n\
+             Host *\n\
+             \x20   ProxyJump bastion\n",
+        );
+
+        assert_eq!(host(&hosts, "a").proxy_jump, ["b"]);
+        assert_eq!(host(&hosts, "b").proxy_jump, ["a"]);
+        // The jump of `Host *` applies to the bastion too.
+        assert!(host(&hosts, "bastion").proxy_jump.is_empty());
+    }
+
+    #[test]
+    fn proxy_commands_are_kept_whole() {
+        let hosts = parse(
+            "Host web\n\
+             \x20   ProxyCommand nc -X 5 -x proxy:1080 %h %p\n\
+             Host direct\n\
+             \x20   ProxyCommand none\n\
+             Host *\n\
+             \x20   ProxyCommand ssh -W %h:%p bastion\n",
+        );
+
+        let web = host(&hosts, "web");
+        assert_eq!(
+            web.proxy_command.as_deref(),
+            Some("nc -X 5 -x proxy:1080 %h %p")
+        );
+        assert_eq!(web.profile().proxy_command, web.proxy_command);
+        assert_eq!(
+            host(&hosts, "direct").proxy_command.as_deref(),
+            Some("none")
+        );
+    }
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob_matches("*", "anything"));
+        assert!(glob_matches("*.example.com", "web.example.com"));
+        assert!(glob_matches("WEB-??", "web-01"));
+        assert!(glob_matches("a*b*c", "axxbyyc"));
+        assert!(!glob_matches("web-?", "web-01"));
+        assert!(!glob_matches("*.example.com", "example.com"));
+    }
+}
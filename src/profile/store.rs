@@ -0,0 +1,524 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use anyhow::{bail, Context};
+use gio::prelude::*;
+use glib::{clone, prelude::*, subclass::prelude::*, subclass::Signal};
+use serde::{Deserialize, Serialize};
+use tracing::{error, trace, warn};
+
+use super::{ansible, share, Defaults, Profile};
+
+/// Version of the profiles file written by this build.
+const VERSION: u32 = 1;
+
+/// Format of a file read-only profiles are read from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SourceKind {
+    /// Profiles shared by other users, see [`share`].
+    #[default]
+    Shared,
+    /// Hosts of an Ansible inventory, see [`ansible`].
+    Ansible,
+}
+
+/// File read-only profiles are read from, and read again when it changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceConfig {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub kind: SourceKind,
+}
+
+impl SourceConfig {
+    /// Name shown next to the profiles of the source.
+    pub fn label(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
+
+    fn read(&self) -> anyhow::Result<Vec<Profile>> {
+        let mut profiles = match self.kind {
+            SourceKind::Shared => share::read(&self.path)?,
+            SourceKind::Ansible => ansible::read(&self.path)?,
+        };
+
+        let label = self.label();
+        for profile in &mut profiles {
+            profile.source = Some(label.clone());
+        }
+        Ok(profiles)
+    }
+}
+
+/// How imported profiles are added to the saved ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Add them, replacing the saved profiles with the same id.
+    Merge,
+    /// Replace every saved profile with them.
+    Replace,
+}
+
+/// Source being watched.
+#[derive(Debug)]
+struct Source {
+    config: SourceConfig,
+    profiles: Vec<Profile>,
+    /// Kept alive for as long as the source is watched.
+    _monitors: Vec<gio::FileMonitor>,
+}
+
+/// Content of the profiles file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProfilesFile {
+    version: u32,
+    #[serde(default)]
+    profiles: Vec<Profile>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sources: Vec<SourceConfig>,
+    /// Defaults of the profiles of each group.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    groups: BTreeMap<String, Defaults>,
+}
+
+impl ProfilesFile {
+    fn parse(text: &str) -> anyhow::Result<Self> {
+        let table: toml::Table = text.parse()?;
+
+        let version = table
+            .get("version")
+            .and_then(|version| version.as_integer())
+            .context("the file has no version")?;
+        if version > VERSION as i64 {
+            bail!(
+                "the file was written by a newer version of Flatline (version {})",
+                version
+            );
+        }
+
+        // Older versions are migrated here as the format evolves.
+        Ok(toml::Value::Table(table).try_into()?)
+    }
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct ProfileStore {
+        pub(super) path: RefCell<PathBuf>,
+
+        pub(super) profiles: RefCell<Vec<Profile>>,
+
+        pub(super) groups: RefCell<BTreeMap<String, Defaults>>,
+
+        /// Read-only profiles, listed after the saved ones.
+        pub(super) sources: RefCell<Vec<Source>>,
+
+        /// Set when the file couldn't be read, so it isn't overwritten with an empty list.
+        pub(super) read_only: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ProfileStore {
+        const NAME: &'static str = "FlatLineProfileStore";
+        type Type = super::ProfileStore;
+    }
+
+    impl ObjectImpl for ProfileStore {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+            SIGNALS.get_or_init(|| vec![Signal::builder("changed").build()])
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Saved profiles, stored in a versioned TOML file.
+    pub struct ProfileStore(ObjectSubclass<imp::ProfileStore>);
+}
+
+impl ProfileStore {
+    /// Default location of the profiles file, under `$XDG_CONFIG_HOME/flatline`.
+    pub fn default_path() -> PathBuf {
+        glib::user_config_dir()
+            .join("flatline")
+            .join("profiles.toml")
+    }
+
+    /// Load the profiles stored at `path`, a missing file holds no profile.
+    pub fn load(path: PathBuf) -> Self {
+        let store: Self = glib::Object::new();
+        store.imp().path.replace(path);
+        store.reload();
+        store
+    }
+
+    /// Read the file again, as it may have been changed by someone else.
+    pub fn reload(&self) {
+        let imp = self.imp();
+        let path = imp.path.borrow().clone();
+
+        let file = match std::fs::read_to_string(&path) {
+            Ok(text) => match ProfilesFile::parse(&text) {
+                Ok(file) => {
+                    imp.read_only.set(false);
+                    file
+                }
+                Err(e) => {
+                    error!("failed to read {} : {:#}", path.display(), e);
+                    imp.read_only.set(true);
+                    ProfilesFile::default()
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                trace!("no profiles file at {}", path.display());
+                imp.read_only.set(false);
+                ProfilesFile::default()
+            }
+            Err(e) => {
+                error!("failed to read {} : {}", path.display(), e);
+                imp.read_only.set(true);
+                ProfilesFile::default()
+            }
+        };
+
+        imp.profiles.replace(file.profiles);
+        imp.groups.replace(file.groups);
+        let sources = file
+            .sources
+            .into_iter()
+            .map(|config| self.watch(config))
+            .collect();
+        imp.sources.replace(sources);
+        self.emit_by_name::<()>("changed", &[]);
+    }
+
+    /// Saved profiles, then the ones of the sources.
+    pub fn profiles(&self) -> Vec<Profile> {
+        let imp = self.imp();
+        let mut profiles = imp.profiles.borrow().clone();
+        for source in imp.sources.borrow().iter() {
+            profiles.extend(source.profiles.iter().cloned());
+        }
+        profiles
+    }
+
+    /// [`Self::profiles`] with the settings they inherit, see [`Self::resolve`].
+    pub fn resolved_profiles(&self) -> Vec<Profile> {
+        self.profiles()
+            .iter()
+            .map(|profile| self.resolve(profile))
+            .collect()
+    }
+
+    pub fn get(&self, id: &str) -> Option<Profile> {
+        let imp = self.imp();
+        let profiles = imp.profiles.borrow();
+        let sources = imp.sources.borrow();
+        profiles
+            .iter()
+            .chain(sources.iter().flat_map(|source| source.profiles.iter()))
+            .find(|profile| profile.id == id)
+            .cloned()
+    }
+
+    /// `profile` with the settings it inherits from its parents and group.
+    pub fn resolve(&self, profile: &Profile) -> Profile {
+        self.resolve_with(profile, &mut Vec::new())
+    }
+
+    fn resolve_with(&self, profile: &Profile, seen: &mut Vec<String>) -> Profile {
+        seen.push(profile.id.clone());
+
+        let mut resolved = profile.clone();
+        let parent = profile
+            .parent
+            .as_deref()
+            .filter(|id| !seen.iter().any(|seen| seen == *id))
+            .and_then(|id| self.get(id));
+        if let Some(parent) = parent {
+            resolved.inherit(&self.resolve_with(&parent, seen).defaults());
+        }
+        if let Some(defaults) = profile
+            .group
+            .as_ref()
+            .and_then(|group| self.imp().groups.borrow().get(group).cloned())
+        {
+            resolved.inherit(&defaults);
+        }
+        resolved
+    }
+
+    /// Settings `profile` would inherit if it didn't set them.
+    pub fn inherited(&self, profile: &Profile) -> Defaults {
+        self.resolve(&profile.without_defaults()).defaults()
+    }
+
+    /// Whether `profile` inherits from the profile `ancestor`, directly or not.
+    pub fn inherits_from(&self, profile: &Profile, ancestor: &str) -> bool {
+        let mut seen = vec![profile.id.clone()];
+        let mut parent = profile.parent.clone();
+        while let Some(id) = parent {
+            if id == ancestor {
+                return true;
+            }
+            if seen.contains(&id) {
+                return false;
+            }
+            parent = self.get(&id).and_then(|profile| profile.parent);
+            seen.push(id);
+        }
+        false
+    }
+
+    pub fn group_defaults(&self, group: &str) -> Defaults {
+        self.imp()
+            .groups
+            .borrow()
+            .get(group)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Set the defaults of the profiles of `group` and save the file.
+    pub fn set_group_defaults(&self, group: &str, defaults: Defaults) -> anyhow::Result<()> {
+        self.update(|imp| {
+            let mut groups = imp.groups.borrow_mut();
+            if defaults.is_empty() {
+                groups.remove(group);
+            } else {
+                groups.insert(group.to_owned(), defaults);
+            }
+        })
+    }
+
+    pub fn sources(&self) -> Vec<SourceConfig> {
+        self.imp()
+            .sources
+            .borrow()
+            .iter()
+            .map(|source| source.config.clone())
+            .collect()
+    }
+
+    /// List the profiles of `config` too, and keep them up to date with the file.
+    pub fn add_source(&self, config: SourceConfig) -> anyhow::Result<()> {
+        if self.sources().contains(&config) {
+            return Ok(());
+        }
+
+        self.check_writable()?;
+        let source = self.watch(config);
+        self.imp().sources.borrow_mut().push(source);
+
+        if let Err(e) = self.save() {
+            self.imp().sources.borrow_mut().pop();
+            return Err(e);
+        }
+        self.emit_by_name::<()>("changed", &[]);
+        Ok(())
+    }
+
+    fn watch(&self, config: SourceConfig) -> Source {
+        Source {
+            profiles: read_source(&config),
+            _monitors: self.monitor(&config),
+            config,
+        }
+    }
+
+    /// Reload the source of `config` when its file changes, or the variables directories of
+    /// an Ansible inventory.
+    fn monitor(&self, config: &SourceConfig) -> Vec<gio::FileMonitor> {
+        let flags = gio::FileMonitorFlags::WATCH_MOVES;
+        let mut monitors = vec![(
+            config.path.clone(),
+            gio::File::for_path(&config.path).monitor_file(flags, gio::Cancellable::NONE),
+        )];
+        if config.kind == SourceKind::Ansible {
+            for dir in ansible::vars_dirs(&config.path) {
+                let monitor =
+                    gio::File::for_path(&dir).monitor_directory(flags, gio::Cancellable::NONE);
+                monitors.push((dir, monitor));
+            }
+        }
+
+        monitors
+            .into_iter()
+            .filter_map(|(watched, monitor)| match monitor {
+                Ok(monitor) => {
+                    let path = config.path.clone();
+                    monitor.connect_changed(clone!(@weak self as store => move |_, _, _, event| {
+                        use gio::FileMonitorEvent::*;
+                        if matches!(event, ChangesDoneHint | Created | Deleted | MovedIn | MovedOut | Renamed) {
+                            store.reload_source(&path);
+                        }
+                    }));
+                    Some(monitor)
+                }
+                Err(e) => {
+                    warn!("failed to watch {} : {}", watched.display(), e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn reload_source(&self, path: &Path) {
+        {
+            let mut sources = self.imp().sources.borrow_mut();
+            let Some(source) = sources.iter_mut().find(|s| s.config.path == path) else {
+                return;
+            };
+
+            trace!("{} changed", path.display());
+            source.profiles = read_source(&source.config);
+            if source.config.kind == SourceKind::Ansible {
+                // Directories of variables may have been added or removed.
+                source._monitors = self.monitor(&source.config);
+            }
+        }
+
+        self.emit_by_name::<()>("changed", &[]);
+    }
+
+    /// Saved profile that `profile` would duplicate, with the same name or destination.
+    pub fn find_conflict(&self, profile: &Profile) -> Option<Profile> {
+        let name = profile.display_name().to_lowercase();
+        self.imp()
+            .profiles
+            .borrow()
+            .iter()
+            .find(|p| {
+                p.display_name().to_lowercase() == name
+                    || (p.host == profile.host && p.port == profile.port && p.user == profile.user)
+            })
+            .cloned()
+    }
+
+    /// Add `profile`, or replace the one with the same id, and save the file.
+    pub fn insert(&self, profile: Profile) -> anyhow::Result<()> {
+        self.insert_all(vec![profile])
+    }
+
+    /// Add profiles read from another file, see [`ImportMode`].
+    pub fn import(&self, profiles: Vec<Profile>, mode: ImportMode) -> anyhow::Result<()> {
+        self.update(|imp| {
+            let mut saved = imp.profiles.borrow_mut();
+            if mode == ImportMode::Replace {
+                saved.clear();
+            }
+            merge(&mut saved, profiles);
+        })
+    }
+
+    /// Add or replace several profiles, saving the file once.
+    pub fn insert_all(&self, profiles: Vec<Profile>) -> anyhow::Result<()> {
+        self.update(|imp| merge(&mut imp.profiles.borrow_mut(), profiles))
+    }
+
+    pub fn remove(&self, id: &str) -> anyhow::Result<()> {
+        self.update(|imp| {
+            imp.profiles.borrow_mut().retain(|profile| profile.id != id);
+        })
+    }
+
+    /// Apply `change` to the saved profiles or groups and save the file, "changed" is only
+    /// emitted once the file is written. The change is undone if it can't be.
+    fn update(&self, change: impl FnOnce(&imp::ProfileStore)) -> anyhow::Result<()> {
+        self.check_writable()?;
+
+        let imp = self.imp();
+        let profiles = imp.profiles.borrow().clone();
+        let groups = imp.groups.borrow().clone();
+        change(imp);
+
+        if let Err(e) = self.save() {
+            imp.profiles.replace(profiles);
+            imp.groups.replace(groups);
+            return Err(e);
+        }
+        self.emit_by_name::<()>("changed", &[]);
+        Ok(())
+    }
+
+    fn check_writable(&self) -> anyhow::Result<()> {
+        let imp = self.imp();
+        if imp.read_only.get() {
+            bail!(
+                "{} couldn't be read, it is not overwritten",
+                imp.path.borrow().display()
+            );
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        self.check_writable()?;
+
+        let imp = self.imp();
+        let path = imp.path.borrow().clone();
+
+        let file = ProfilesFile {
+            version: VERSION,
+            profiles: imp.profiles.borrow().clone(),
+            sources: self.sources(),
+            groups: imp.groups.borrow().clone(),
+        };
+        write_atomically(&path, &toml::to_string_pretty(&file)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    pub fn connect_changed<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_local("changed", false, move |values| {
+            match values[0].get::<Self>() {
+                Ok(store) => f(&store),
+                Err(e) => warn!("unexpected changed signal : {}", e),
+            }
+            None
+        })
+    }
+}
+
+/// Add `new_profiles` to `profiles`, replacing the ones with the same id.
+fn merge(profiles: &mut Vec<Profile>, new_profiles: Vec<Profile>) {
+    for profile in new_profiles {
+        match profiles.iter_mut().find(|p| p.id == profile.id) {
+            Some(existing) => *existing = profile,
+            None => profiles.push(profile),
+        }
+    }
+}
+
+/// Profiles of `config`, none if it can't be read, as it may be back later.
+fn read_source(config: &SourceConfig) -> Vec<Profile> {
+    match config.read() {
+        Ok(profiles) => profiles,
+        Err(e) => {
+            warn!("failed to read {} : {:#}", config.path.display(), e);
+            Vec::new()
+        }
+    }
+}
+
+/// Write `content` to a temporary file next to `path` then move it over, so a crash can't
+/// leave a truncated file behind.
+pub(super) fn write_atomically(path: &Path, content: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(tmp, path)
+}
@@ -0,0 +1,785 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use adw::{prelude::*, subclass::prelude::*};
+use flatline_session::ConnectOptions;
+use glib::clone;
+use gtk::{gdk, gio, glib, CompositeTemplate};
+use tokio::sync::oneshot;
+use tracing::{trace, warn};
+
+use crate::{
+    application::Application,
+    profile::{expand_home, Appearance, AuthKind, Defaults, Profile},
+};
+
+/// Authentication methods, in the order of the method combo row.
+const AUTH_KINDS: [AuthKind; 4] = [
+    AuthKind::Auto,
+    AuthKind::Agent,
+    AuthKind::Password,
+    AuthKind::IdentityFile,
+];
+const AUTH_LABELS: [&str; 4] = ["Automatic", "SSH Agent", "Password", "Identity File"];
+
+const ENV_DESCRIPTION: &str = "Variables set on the server, as NAME=value";
+
+/// What the user chose to do with the edited profile.
+#[derive(Debug, Clone)]
+pub enum EditorResult {
+    /// Keep it in the profile store.
+    Save(Profile),
+    /// Connect with it once, without saving it.
+    ConnectOnce(Profile),
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(file = "gtk/profile_editor.ui")]
+    pub struct ProfileEditor {
+        #[template_child]
+        name_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        group_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        parent_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        tags_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        host_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        port_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        user_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        user_inherited: TemplateChild<gtk::Label>,
+        #[template_child]
+        user_reset: TemplateChild<gtk::Button>,
+        #[template_child]
+        auth_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        auth_inherited: TemplateChild<gtk::Label>,
+        #[template_child]
+        auth_reset: TemplateChild<gtk::Button>,
+        #[template_child]
+        identity_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        identity_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        jump_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        jump_inherited: TemplateChild<gtk::Label>,
+        #[template_child]
+        jump_reset: TemplateChild<gtk::Button>,
+        #[template_child]
+        proxy_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        proxy_inherited: TemplateChild<gtk::Label>,
+        #[template_child]
+        proxy_reset: TemplateChild<gtk::Button>,
+        #[template_child]
+        font_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        font_inherited: TemplateChild<gtk::Label>,
+        #[template_child]
+        font_reset: TemplateChild<gtk::Button>,
+        #[template_child]
+        foreground_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        foreground_inherited: TemplateChild<gtk::Label>,
+        #[template_child]
+        foreground_reset: TemplateChild<gtk::Button>,
+        #[template_child]
+        background_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        background_inherited: TemplateChild<gtk::Label>,
+        #[template_child]
+        background_reset: TemplateChild<gtk::Button>,
+        #[template_child]
+        stderr_color_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        stderr_color_inherited: TemplateChild<gtk::Label>,
+        #[template_child]
+        stderr_color_reset: TemplateChild<gtk::Button>,
+        #[template_child]
+        env_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        add_env_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        connect_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        save_button: TemplateChild<gtk::Button>,
+
+        /// Profile being edited, so the fields the editor doesn't show are kept.
+        profile: RefCell<Profile>,
+
+        /// Ids of the profiles of the parent row, after "None".
+        parents: RefCell<Vec<String>>,
+
+        /// Set when editing the defaults of a group rather than a profile.
+        pub(super) group_mode: Cell<bool>,
+
+        identity_file: RefCell<Option<PathBuf>>,
+
+        env_rows: RefCell<Vec<adw::EntryRow>>,
+
+        pub(super) result: RefCell<Option<oneshot::Sender<Option<EditorResult>>>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ProfileEditor {
+        const NAME: &'static str = "FlatLineProfileEditor";
+        type Type = super::ProfileEditor;
+        type ParentType = adw::PreferencesWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ProfileEditor {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = &*self.obj();
+
+            for row in [
+                &*self.name_row,
+                &*self.group_row,
+                &*self.tags_row,
+                &*self.host_row,
+                &*self.user_row,
+                &*self.jump_row,
+                &*self.proxy_row,
+                &*self.font_row,
+                &*self.foreground_row,
+                &*self.background_row,
+                &*self.stderr_color_row,
+            ] {
+                row.connect_changed(clone!(@weak obj => move |_| {
+                    obj.imp().validate();
+                }));
+            }
+            self.port_row
+                .connect_value_notify(clone!(@weak obj => move |_| {
+                    obj.imp().validate();
+                }));
+            self.auth_row
+                .connect_selected_notify(clone!(@weak obj => move |_| {
+                    obj.imp().validate();
+                }));
+            self.parent_row
+                .connect_selected_notify(clone!(@weak obj => move |_| {
+                    obj.imp().validate();
+                }));
+
+            self.user_reset
+                .connect_clicked(clone!(@weak obj => move |_| {
+                    obj.imp().user_row.set_text("");
+                }));
+            self.auth_reset
+                .connect_clicked(clone!(@weak obj => move |_| {
+                    obj.imp().auth_row.set_selected(0);
+                }));
+            self.jump_reset
+                .connect_clicked(clone!(@weak obj => move |_| {
+                    obj.imp().jump_row.set_text("");
+                }));
+            for (row, reset) in [
+                (&*self.proxy_row, &*self.proxy_reset),
+                (&*self.font_row, &*self.font_reset),
+                (&*self.foreground_row, &*self.foreground_reset),
+                (&*self.background_row, &*self.background_reset),
+                (&*self.stderr_color_row, &*self.stderr_color_reset),
+            ] {
+                reset.connect_clicked(clone!(@weak row => move |_| {
+                    row.set_text("");
+                }));
+            }
+
+            self.identity_button
+                .connect_clicked(clone!(@weak obj => move |_| {
+                    glib::MainContext::default().spawn_local(clone!(@weak obj => async move {
+                        obj.imp().choose_identity_file().await;
+                    }));
+                }));
+
+            self.add_env_button
+                .connect_clicked(clone!(@weak obj => move |_| {
+                    obj.imp().add_env_row("");
+                }));
+
+            self.connect_button
+                .connect_clicked(clone!(@weak obj => move |_| {
+                    if let Some(profile) = obj.imp().read() {
+                        obj.imp().finish(Some(EditorResult::ConnectOnce(profile)));
+                        obj.close();
+                    }
+                }));
+
+            self.save_button
+                .connect_clicked(clone!(@weak obj => move |_| {
+                    if let Some(profile) = obj.imp().read() {
+                        obj.imp().finish(Some(EditorResult::Save(profile)));
+                        obj.close();
+                    }
+                }));
+        }
+    }
+
+    impl WidgetImpl for ProfileEditor {}
+
+    impl WindowImpl for ProfileEditor {
+        fn close_request(&self) -> glib::Propagation {
+            self.finish(None);
+            self.parent_close_request()
+        }
+    }
+
+    impl AdwWindowImpl for ProfileEditor {}
+
+    impl PreferencesWindowImpl for ProfileEditor {}
+
+    impl ProfileEditor {
+        pub(super) fn load(&self, profile: Profile) {
+            let group_mode = self.group_mode.get();
+            for row in [
+                self.name_row.upcast_ref::<gtk::Widget>(),
+                self.group_row.upcast_ref(),
+                self.parent_row.upcast_ref(),
+                self.tags_row.upcast_ref(),
+                self.host_row.upcast_ref(),
+                self.port_row.upcast_ref(),
+                self.connect_button.upcast_ref(),
+            ] {
+                row.set_visible(!group_mode);
+            }
+
+            // A profile can't inherit from itself, even through others.
+            let store = Application::get().profiles();
+            let mut parents: Vec<Profile> = store
+                .profiles()
+                .into_iter()
+                .filter(|p| p.id != profile.id && !store.inherits_from(p, &profile.id))
+                .collect();
+            parents.sort_by_cached_key(|p| p.display_name().to_lowercase());
+            let names: Vec<String> = std::iter::once("None".to_owned())
+                .chain(parents.iter().map(Profile::display_name))
+                .collect();
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+            self.parent_row
+                .set_model(Some(&gtk::StringList::new(&names)));
+            let parent = profile
+                .parent
+                .as_ref()
+                .and_then(|id| parents.iter().position(|p| &p.id == id))
+                .map_or(0, |index| index + 1);
+            self.parents
+                .replace(parents.into_iter().map(|p| p.id).collect());
+            self.parent_row.set_selected(parent as u32);
+
+            self.name_row.set_text(&profile.name);
+            self.group_row
+                .set_text(profile.group.as_deref().unwrap_or_default());
+            self.tags_row.set_text(&profile.tags.join(", "));
+            self.host_row.set_text(&profile.host);
+            self.port_row.set_value(profile.port.into());
+            self.user_row
+                .set_text(profile.user.as_deref().unwrap_or_default());
+
+            let auth = AUTH_KINDS
+                .iter()
+                .position(|kind| *kind == profile.auth)
+                .unwrap_or_default();
+            self.auth_row.set_selected(auth as u32);
+            self.set_identity_file(profile.identity_file.clone());
+
+            self.jump_row.set_text(&profile.jump_hosts.join(", "));
+            self.proxy_row
+                .set_text(profile.proxy_command.as_deref().unwrap_or_default());
+            let appearance = &profile.appearance;
+            for (row, value) in [
+                (&self.font_row, &appearance.font),
+                (&self.foreground_row, &appearance.foreground),
+                (&self.background_row, &appearance.background),
+                (&self.stderr_color_row, &appearance.stderr_color),
+            ] {
+                row.set_text(value.as_deref().unwrap_or_default());
+            }
+            for (name, value) in &profile.env {
+                self.add_env_row(&format!("{}={}", name, value));
+            }
+
+            self.profile.replace(profile);
+            self.validate();
+        }
+
+        pub(super) fn finish(&self, result: Option<EditorResult>) {
+            if let Some(sender) = self.result.take() {
+                if sender.send(result).is_err() {
+                    trace!("nobody is waiting for the editor");
+                }
+            }
+        }
+
+        fn set_identity_file(&self, path: Option<PathBuf>) {
+            let subtitle = path
+                .as_deref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| "None".to_owned());
+            self.identity_row
+                .set_subtitle(&glib::markup_escape_text(&subtitle));
+            self.identity_file.replace(path);
+        }
+
+        async fn choose_identity_file(&self) {
+            let dialog = gtk::FileDialog::builder()
+                .title("Choose a Private Key")
+                .modal(true)
+                .build();
+
+            let ssh_dir = glib::home_dir().join(".ssh");
+            if ssh_dir.is_dir() {
+                dialog.set_initial_folder(Some(&gio::File::for_path(ssh_dir)));
+            }
+
+            match dialog.open_future(Some(&*self.obj())).await {
+                Ok(file) => {
+                    self.set_identity_file(file.path());
+                    self.validate();
+                }
+                Err(e) => trace!("no identity file chosen : {}", e),
+            }
+        }
+
+        fn add_env_row(&self, text: &str) {
+            let row = adw::EntryRow::builder()
+                .title("Variable")
+                .text(text)
+                .build();
+
+            let remove = gtk::Button::builder()
+                .icon_name("list-remove-symbolic")
+                .tooltip_text("Remove")
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .build();
+            row.add_suffix(&remove);
+
+            let obj = &*self.obj();
+            row.connect_changed(clone!(@weak obj => move |_| {
+                obj.imp().validate();
+            }));
+            remove.connect_clicked(clone!(@weak obj, @weak row => move |_| {
+                let imp = obj.imp();
+                imp.env_group.remove(&row);
+                imp.env_rows.borrow_mut().retain(|r| r != &row);
+                imp.validate();
+            }));
+
+            self.env_group.add(&row);
+            self.env_rows.borrow_mut().push(row);
+        }
+
+        fn validate(&self) {
+            self.highlight();
+            let valid = self.read().is_some();
+            self.connect_button.set_sensitive(valid);
+            self.save_button.set_sensitive(valid);
+            self.show_inherited();
+        }
+
+        /// Show the settings the profile inherits, and the ones it overrides.
+        fn show_inherited(&self) {
+            let inherited = if self.group_mode.get() {
+                Defaults::default()
+            } else {
+                let group = self.group_row.text().trim().to_owned();
+                let profile = Profile {
+                    parent: self.parent(),
+                    group: Some(group).filter(|group| !group.is_empty()),
+                    ..self.profile.borrow().clone()
+                };
+                Application::get().profiles().inherited(&profile)
+            };
+
+            let user = self.user_row.text().trim().to_owned();
+            show_override(
+                &self.user_inherited,
+                &self.user_reset,
+                !user.is_empty(),
+                inherited.user,
+            );
+
+            let auth = self.auth_row.selected() as usize;
+            let inherited_auth = match inherited.auth {
+                AuthKind::Auto => None,
+                AuthKind::IdentityFile => Some(format!(
+                    "{} ({})",
+                    auth_label(AuthKind::IdentityFile),
+                    inherited
+                        .identity_file
+                        .as_deref()
+                        .map(|path| path.display().to_string())
+                        .unwrap_or_default()
+                )),
+                kind => Some(auth_label(kind).to_owned()),
+            };
+            show_override(
+                &self.auth_inherited,
+                &self.auth_reset,
+                auth != 0,
+                inherited_auth,
+            );
+
+            let jump = self.jump_row.text().trim().to_owned();
+            show_override(
+                &self.jump_inherited,
+                &self.jump_reset,
+                !jump.is_empty(),
+                Some(inherited.jump_hosts.join(", ")).filter(|jump| !jump.is_empty()),
+            );
+            show_override(
+                &self.proxy_inherited,
+                &self.proxy_reset,
+                !self.proxy_row.text().trim().is_empty(),
+                inherited.proxy_command,
+            );
+
+            let appearance = &inherited.appearance;
+            for (row, label, reset, inherited) in [
+                (
+                    &self.font_row,
+                    &self.font_inherited,
+                    &self.font_reset,
+                    &appearance.font,
+                ),
+                (
+                    &self.foreground_row,
+                    &self.foreground_inherited,
+                    &self.foreground_reset,
+                    &appearance.foreground,
+                ),
+                (
+                    &self.background_row,
+                    &self.background_inherited,
+                    &self.background_reset,
+                    &appearance.background,
+                ),
+                (
+                    &self.stderr_color_row,
+                    &self.stderr_color_inherited,
+                    &self.stderr_color_reset,
+                    &appearance.stderr_color,
+                ),
+            ] {
+                show_override(
+                    label,
+                    reset,
+                    !row.text().trim().is_empty(),
+                    inherited.clone(),
+                );
+            }
+
+            if inherited.env.is_empty() {
+                self.env_group.set_description(Some(ENV_DESCRIPTION));
+            } else {
+                let names: Vec<&str> = inherited.env.keys().map(String::as_str).collect();
+                self.env_group.set_description(Some(&format!(
+                    "{}, inherits {}",
+                    ENV_DESCRIPTION,
+                    names.join(", ")
+                )));
+            }
+            for row in self.env_rows.borrow().iter() {
+                let overrides = parse_variable(row.text().trim())
+                    .is_some_and(|(name, _)| inherited.env.contains_key(name));
+                row.set_title(if overrides {
+                    "Variable, overrides the inherited value"
+                } else {
+                    "Variable"
+                });
+            }
+        }
+
+        /// Id of the profile chosen in the parent row.
+        fn parent(&self) -> Option<String> {
+            let index = (self.parent_row.selected() as usize).checked_sub(1)?;
+            self.parents.borrow().get(index).cloned()
+        }
+
+        /// Highlight the invalid fields, empty ones are not as the user may not have filled
+        /// them yet, and only show the identity file when it is used.
+        fn highlight(&self) {
+            let host = self.host_row.text().trim().to_owned();
+            mark_error(
+                &*self.host_row,
+                !self.group_mode.get() && !host.is_empty() && !is_host(&host),
+            );
+
+            mark_error(&*self.user_row, !is_user(self.user_row.text().trim()));
+
+            let needs_identity = self.auth() == AuthKind::IdentityFile;
+            self.identity_row.set_visible(needs_identity);
+            mark_error(&*self.identity_row, needs_identity && !self.identity_ok());
+
+            mark_error(
+                &*self.jump_row,
+                parse_jump_hosts(&self.jump_row.text()).is_none(),
+            );
+
+            for row in [
+                &*self.foreground_row,
+                &*self.background_row,
+                &*self.stderr_color_row,
+            ] {
+                mark_error(row, parse_color(row).is_none());
+            }
+
+            for row in self.env_rows.borrow().iter() {
+                let text = row.text();
+                let text = text.trim();
+                mark_error(row, !text.is_empty() && parse_variable(text).is_none());
+            }
+        }
+
+        fn auth(&self) -> AuthKind {
+            AUTH_KINDS
+                .get(self.auth_row.selected() as usize)
+                .copied()
+                .unwrap_or_default()
+        }
+
+        fn identity_ok(&self) -> bool {
+            self.identity_file.borrow().as_deref().is_some_and(is_file)
+        }
+
+        /// Profile described by the fields, `None` if one of them is invalid.
+        fn read(&self) -> Option<Profile> {
+            let mut profile = self.profile.borrow().clone();
+
+            profile.name = self.name_row.text().trim().to_owned();
+
+            let group = self.group_row.text().trim().to_owned();
+            profile.group = Some(group).filter(|group| !group.is_empty());
+            profile.parent = self.parent();
+
+            profile.tags.clear();
+            for tag in self.tags_row.text().split(',').map(str::trim) {
+                if !tag.is_empty() && !profile.tags.iter().any(|t| t == tag) {
+                    profile.tags.push(tag.to_owned());
+                }
+            }
+
+            let host = self.host_row.text().trim().to_owned();
+            if !self.group_mode.get() && !is_host(&host) {
+                return None;
+            }
+            profile.host = host;
+
+            profile.port = self.port_row.value() as u16;
+
+            let user = self.user_row.text().trim().to_owned();
+            if !is_user(&user) {
+                return None;
+            }
+            profile.user = Some(user).filter(|user| !user.is_empty());
+
+            profile.auth = self.auth();
+            if profile.auth == AuthKind::IdentityFile && !self.identity_ok() {
+                return None;
+            }
+            profile.identity_file = self.identity_file.borrow().clone();
+
+            profile.jump_hosts = parse_jump_hosts(&self.jump_row.text())?;
+            profile.proxy_command = entry_text(&self.proxy_row);
+
+            profile.appearance = Appearance {
+                font: entry_text(&self.font_row),
+                foreground: parse_color(&self.foreground_row)?,
+                background: parse_color(&self.background_row)?,
+                stderr_color: parse_color(&self.stderr_color_row)?,
+            };
+
+            let mut env = BTreeMap::new();
+            for row in self.env_rows.borrow().iter() {
+                let text = row.text();
+                let text = text.trim();
+                if text.is_empty() {
+                    continue;
+                }
+
+                let (name, value) = parse_variable(text)?;
+                env.insert(name.to_owned(), value.to_owned());
+            }
+            profile.env = env;
+
+            Some(profile)
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Dialog creating or editing a [`Profile`].
+    pub struct ProfileEditor(ObjectSubclass<imp::ProfileEditor>)
+        @extends adw::PreferencesWindow, adw::Window, gtk::Window, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Native,
+            gtk::Root, gtk::ShortcutManager;
+}
+
+impl ProfileEditor {
+    /// Edit `profile`, or a new profile, in a dialog over `parent`, `None` if it was cancelled.
+    pub async fn run(parent: &gtk::Widget, profile: Option<Profile>) -> Option<EditorResult> {
+        let editor: Self = glib::Object::new();
+        let imp = editor.imp();
+
+        if profile.is_some() {
+            editor.set_title(Some("Edit Connection"));
+        }
+        imp.load(profile.unwrap_or_default());
+
+        editor.wait(parent).await
+    }
+
+    /// Edit the defaults of the profiles of `group`, `None` if it was cancelled.
+    pub async fn run_group(
+        parent: &gtk::Widget,
+        group: &str,
+        defaults: Defaults,
+    ) -> Option<Defaults> {
+        let editor: Self = glib::Object::new();
+        let imp = editor.imp();
+
+        editor.set_title(Some(&format!("Defaults of {}", group)));
+        imp.group_mode.set(true);
+        let mut profile = Profile::default();
+        profile.inherit(&defaults);
+        imp.load(profile);
+
+        match editor.wait(parent).await? {
+            EditorResult::Save(profile) | EditorResult::ConnectOnce(profile) => {
+                Some(profile.defaults())
+            }
+        }
+    }
+
+    async fn wait(&self, parent: &gtk::Widget) -> Option<EditorResult> {
+        let window = parent.root().and_downcast::<gtk::Window>();
+        self.set_transient_for(window.as_ref());
+
+        let (sender, receiver) = oneshot::channel();
+        self.imp().result.replace(Some(sender));
+        self.present();
+
+        match receiver.await {
+            Ok(result) => result,
+            Err(e) => {
+                warn!("the editor was dropped : {}", e);
+                None
+            }
+        }
+    }
+}
+
+fn auth_label(kind: AuthKind) -> &'static str {
+    AUTH_KINDS
+        .iter()
+        .position(|k| *k == kind)
+        .map_or(AUTH_LABELS[0], |index| AUTH_LABELS[index])
+}
+
+/// Show the inherited value of a setting next to it, and a button to inherit it again when
+/// the profile overrides it.
+fn show_override(
+    label: &gtk::Label,
+    reset: &gtk::Button,
+    overridden: bool,
+    inherited: Option<String>,
+) {
+    let Some(inherited) = inherited else {
+        label.set_visible(false);
+        reset.set_visible(false);
+        return;
+    };
+
+    if overridden {
+        label.set_label(&format!("Overrides {}", inherited));
+        label.remove_css_class("dim-label");
+        label.add_css_class("accent");
+    } else {
+        label.set_label(&format!("Inherits {}", inherited));
+        label.remove_css_class("accent");
+        label.add_css_class("dim-label");
+    }
+    label.set_visible(true);
+    reset.set_visible(overridden);
+}
+
+fn mark_error(row: &impl IsA<gtk::Widget>, error: bool) {
+    if error {
+        row.add_css_class("error");
+    } else {
+        row.remove_css_class("error");
+    }
+}
+
+fn is_host(host: &str) -> bool {
+    !host.is_empty() && !host.contains(|c: char| c.is_whitespace() || c == '@' || c == '/')
+}
+
+/// Text of `row`, `None` if it is empty.
+fn entry_text(row: &adw::EntryRow) -> Option<String> {
+    let text = row.text().trim().to_owned();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Color of `row`, `Some(None)` if it is empty and `None` if it isn't a color.
+fn parse_color(row: &adw::EntryRow) -> Option<Option<String>> {
+    match entry_text(row) {
+        Some(color) => gdk::RGBA::parse(&color).is_ok().then_some(Some(color)),
+        None => Some(None),
+    }
+}
+
+fn is_user(user: &str) -> bool {
+    !user.contains(|c: char| c.is_whitespace() || c == '@')
+}
+
+/// Jump hosts separated by commas, `None` if one of them isn't a valid destination.
+fn parse_jump_hosts(text: &str) -> Option<Vec<String>> {
+    let jump_hosts: Vec<String> = text
+        .split(',')
+        .map(|jump| jump.trim().to_owned())
+        .filter(|jump| !jump.is_empty())
+        .collect();
+
+    jump_hosts
+        .iter()
+        .all(|jump| jump.parse::<ConnectOptions>().is_ok())
+        .then_some(jump_hosts)
+}
+
+fn is_file(path: &Path) -> bool {
+    expand_home(path).is_file()
+}
+
+/// Split `NAME=value`, `None` if the name isn't a valid variable name.
+fn parse_variable(text: &str) -> Option<(&str, &str)> {
+    let (name, value) = text.split_once('=')?;
+    let name = name.trim();
+
+    let mut chars = name.chars();
+    let first = chars.next()?;
+    let valid = (first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    valid.then_some((name, value))
+}
@@ -0,0 +1,283 @@
+use std::cell::{OnceCell, RefCell};
+
+use adw::{prelude::*, subclass::prelude::*};
+use flatline_session::ConnectOptions;
+use glib::clone;
+use gtk::{gdk, glib};
+use tracing::warn;
+
+use crate::{
+    application::Application,
+    profile::{ssh_config, SshHost},
+};
+
+/// Most suggestions shown under the entry.
+const MAX_SUGGESTIONS: usize = 8;
+
+/// Completion offered for what was typed, activating it runs `action` with `target`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Suggestion {
+    title: String,
+    subtitle: String,
+    action: &'static str,
+    target: String,
+}
+
+impl Suggestion {
+    fn matches(&self, query: &str) -> bool {
+        self.title.to_lowercase().contains(query) || self.subtitle.to_lowercase().contains(query)
+    }
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct QuickConnect {
+        pub(super) entry: OnceCell<gtk::Entry>,
+
+        pub(super) popover: OnceCell<gtk::Popover>,
+
+        pub(super) list: OnceCell<gtk::ListBox>,
+
+        /// Hosts of `~/.ssh/config`, read once per page.
+        pub(super) ssh_hosts: RefCell<Vec<SshHost>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for QuickConnect {
+        const NAME: &'static str = "FlatLineQuickConnect";
+        type Type = super::QuickConnect;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_layout_manager_type::<gtk::BinLayout>();
+        }
+    }
+
+    impl ObjectImpl for QuickConnect {
+        fn dispose(&self) {
+            // The popover is parented to the entry, which doesn't unparent it by itself.
+            if let Some(popover) = self.popover.get() {
+                popover.unparent();
+            }
+
+            while let Some(child) = self.obj().first_child() {
+                child.unparent();
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = &*self.obj();
+
+            let entry = gtk::Entry::builder()
+                .placeholder_text("user@host:port")
+                .primary_icon_name("network-server-symbolic")
+                .input_purpose(gtk::InputPurpose::Url)
+                .tooltip_text("Connect to a destination or an ssh:// URI")
+                .build();
+            entry.set_parent(obj);
+
+            let list = gtk::ListBox::builder()
+                .selection_mode(gtk::SelectionMode::None)
+                .build();
+            let popover = gtk::Popover::builder()
+                .autohide(false)
+                .has_arrow(false)
+                .position(gtk::PositionType::Bottom)
+                .halign(gtk::Align::Start)
+                .child(&list)
+                .build();
+            popover.set_parent(&entry);
+
+            entry.connect_changed(clone!(@weak obj => move |entry| {
+                entry.remove_css_class("error");
+                obj.imp().update_suggestions();
+            }));
+
+            entry.connect_activate(clone!(@weak obj => move |_| {
+                obj.imp().connect();
+            }));
+
+            let keys = gtk::EventControllerKey::new();
+            keys.connect_key_pressed(clone!(@weak obj => @default-return glib::Propagation::Proceed, move |_, key, _, _| {
+                obj.imp().key_pressed(key)
+            }));
+            entry.add_controller(keys);
+
+            let focus = gtk::EventControllerFocus::new();
+            focus.connect_leave(clone!(@weak popover => move |_| {
+                popover.popdown();
+            }));
+            entry.add_controller(focus);
+
+            self.ssh_hosts.replace(ssh_config::user_hosts());
+
+            let _ = self.entry.set(entry);
+            let _ = self.popover.set(popover);
+            let _ = self.list.set(list);
+        }
+    }
+
+    impl WidgetImpl for QuickConnect {
+        fn grab_focus(&self) -> bool {
+            match self.entry.get() {
+                Some(entry) => entry.grab_focus(),
+                None => self.parent_grab_focus(),
+            }
+        }
+    }
+
+    impl QuickConnect {
+        fn entry(&self) -> &gtk::Entry {
+            self.entry
+                .get()
+                .expect("the entry is created on construction")
+        }
+
+        fn popover(&self) -> &gtk::Popover {
+            self.popover
+                .get()
+                .expect("the popover is created on construction")
+        }
+
+        fn list(&self) -> &gtk::ListBox {
+            self.list
+                .get()
+                .expect("the list is created on construction")
+        }
+
+        fn key_pressed(&self, key: gdk::Key) -> glib::Propagation {
+            let popover = self.popover();
+            if !popover.is_visible() {
+                return glib::Propagation::Proceed;
+            }
+
+            match key {
+                gdk::Key::Down => {
+                    if let Some(row) = self.list().row_at_index(0) {
+                        row.grab_focus();
+                    }
+                    glib::Propagation::Stop
+                }
+                gdk::Key::Escape => {
+                    popover.popdown();
+                    glib::Propagation::Stop
+                }
+                _ => glib::Propagation::Proceed,
+            }
+        }
+
+        fn connect(&self) {
+            let entry = self.entry();
+            let destination = entry.text();
+            let destination = destination.trim();
+            if destination.is_empty() {
+                return;
+            }
+
+            if destination.parse::<ConnectOptions>().is_err() {
+                entry.add_css_class("error");
+                return;
+            }
+
+            self.popover().popdown();
+            if let Err(e) = self
+                .obj()
+                .activate_action("pane.quick-connect", Some(&destination.to_variant()))
+            {
+                warn!("failed to quick connect : {}", e);
+            }
+        }
+
+        /// Candidates from the history, the saved profiles and the ssh configuration.
+        fn candidates(&self) -> Vec<Suggestion> {
+            let app = Application::get();
+            let mut candidates = Vec::new();
+
+            for entry in app.history().entries() {
+                if entry.profile.is_none() {
+                    candidates.push(Suggestion {
+                        title: entry.destination.clone(),
+                        subtitle: "Recent".to_owned(),
+                        action: "pane.quick-connect",
+                        target: entry.destination,
+                    });
+                }
+            }
+
+            for profile in app.profiles().resolved_profiles() {
+                candidates.push(Suggestion {
+                    title: profile.display_name(),
+                    subtitle: profile.destination(),
+                    action: "pane.connect-profile",
+                    target: profile.id,
+                });
+            }
+
+            for host in self.ssh_hosts.borrow().iter() {
+                candidates.push(Suggestion {
+                    title: host.alias.clone(),
+                    subtitle: host
+                        .host_name
+                        .clone()
+                        .unwrap_or_else(|| "SSH Configuration".to_owned()),
+                    action: "pane.quick-connect",
+                    target: host.alias.clone(),
+                });
+            }
+
+            candidates
+        }
+
+        fn update_suggestions(&self) {
+            let query = self.entry().text().trim().to_lowercase();
+            let list = self.list();
+            list.remove_all();
+
+            if query.is_empty() {
+                self.popover().popdown();
+                return;
+            }
+
+            let mut shown: Vec<Suggestion> = Vec::new();
+            for suggestion in self.candidates() {
+                if shown.len() == MAX_SUGGESTIONS {
+                    break;
+                }
+                if !suggestion.matches(&query) || shown.contains(&suggestion) {
+                    continue;
+                }
+
+                let row = adw::ActionRow::builder()
+                    .title(glib::markup_escape_text(&suggestion.title))
+                    .subtitle(glib::markup_escape_text(&suggestion.subtitle))
+                    .activatable(true)
+                    .action_name(suggestion.action)
+                    .action_target(&suggestion.target.to_variant())
+                    .build();
+                list.append(&row);
+                shown.push(suggestion);
+            }
+
+            if shown.is_empty() {
+                self.popover().popdown();
+            } else {
+                self.popover().popup();
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Entry connecting to a typed destination, with completion.
+    pub struct QuickConnect(ObjectSubclass<imp::QuickConnect>)
+        @extends gtk::Widget;
+}
+
+impl Default for QuickConnect {
+    fn default() -> Self {
+        glib::Object::new()
+    }
+}
@@ -1,27 +1,49 @@
 use std::{
-    cell::{OnceCell, RefCell},
-    thread::JoinHandle,
+    cell::{Cell, OnceCell, RefCell},
+    collections::HashMap,
+    path::PathBuf,
+    time::Duration,
 };
 
 use anyhow::{Context, Ok};
+use flatline_session::{
+    AuthPrompt, ConnectOptions, Control, Error, Reply, SessionEnd, SessionEvent, SessionOptions,
+    TermSize,
+};
 use glib::{
+    clone,
     subclass::{
         prelude::{DerivedObjectProperties, ObjectImpl, ObjectImplExt},
-        types::{ObjectSubclass, ObjectSubclassExt},
+        types::{ObjectSubclass, ObjectSubclassExt, ObjectSubclassIsExt},
     },
-    ObjectExt,
+    Cast, ObjectExt,
 };
 use gtk::{
+    gdk,
     subclass::widget::{WidgetClassExt, WidgetImpl},
-    Box,
+    Box, Button, Label, Overlay, Revealer, Stack,
+};
+use tokio::sync::{
+    mpsc::{self, Sender},
+    watch,
 };
-use tokio::sync::mpsc::{self, Sender};
-use tracing::{error, warn};
+use tracing::{error, trace, warn};
 use vte4::{BoxExt, Pty, Terminal, TerminalExt, WidgetExt};
 
-pub enum RemotePaneMsg {
-    Close,
-    SizeChanged(i32, i32),
+use super::ConnectionState;
+use crate::application::Application;
+
+/// Delay during which size changes are grouped, so dragging the window doesn't flood the server.
+const RESIZE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Longest delay between two reconnection attempts.
+const MAX_RECONNECT_DELAY: u32 = 60;
+
+/// What a secret typed by the user unlocks.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum SecretKind {
+    Password { user: String },
+    Passphrase { path: PathBuf },
 }
 
 #[derive(glib::Properties)]
@@ -38,11 +60,57 @@ pub struct RemotePane {
     #[property(get, set)]
     title: RefCell<String>,
 
-    thread_handle: RefCell<Option<JoinHandle<()>>>,
+    /// Color used to tint stderr output, or `None` to print it as is.
+    #[property(get, set)]
+    stderr_color: RefCell<Option<gdk::RGBA>>,
+
+    #[property(get, builder(ConnectionState::default()))]
+    state: Cell<ConnectionState>,
+
+    #[property(get)]
+    bytes_sent: Cell<u64>,
+
+    #[property(get)]
+    bytes_received: Cell<u64>,
+
+    /// Secrets typed by the user, reused by reconnections until they are refused. They are
+    /// only ever kept in memory, for the life of the pane.
+    secrets: RefCell<HashMap<SecretKind, String>>,
+
+    /// Number of automatic reconnection attempts before giving up.
+    #[property(get, set)]
+    max_reconnect_attempts: Cell<u32>,
+
+    stack: Stack,
+
+    error_page: adw::StatusPage,
+
+    exit_banner: Revealer,
+
+    exit_label: Label,
+
+    reconnect_overlay: Box,
+
+    reconnect_label: Label,
+
+    reconnect_attempt: Cell<u32>,
+
+    reconnect_countdown: Cell<u32>,
+
+    reconnect_source: RefCell<Option<glib::SourceId>>,
+
+    last_end: RefCell<Option<SessionEnd>>,
 
-    size: RefCell<(i32, i32)>,
+    size_sender: RefCell<Option<watch::Sender<TermSize>>>,
 
-    sender: OnceCell<Sender<RemotePaneMsg>>,
+    resize_source: RefCell<Option<glib::SourceId>>,
+
+    pub(super) options: RefCell<SessionOptions>,
+
+    /// Everything needed to connect except the host and port.
+    pub(super) connect: RefCell<ConnectOptions>,
+
+    sender: RefCell<Option<Sender<Control>>>,
 }
 
 impl Default for RemotePane {
@@ -53,14 +121,93 @@ impl Default for RemotePane {
             .enable_sixel(true)
             .build();
 
+        let exit_label = Label::builder().hexpand(true).xalign(0.0).build();
+
+        let exit_bar = Box::builder()
+            .spacing(6)
+            .margin_start(12)
+            .margin_end(6)
+            .margin_top(6)
+            .margin_bottom(6)
+            .build();
+        exit_bar.append(&exit_label);
+        exit_bar.append(
+            &Button::builder()
+                .label("Reconnect")
+                .action_name("remote-pane.reconnect")
+                .css_classes(["suggested-action"])
+                .build(),
+        );
+        exit_bar.append(
+            &Button::builder()
+                .label("Close")
+                .action_name("pane.close")
+                .build(),
+        );
+
+        let exit_banner = Revealer::builder()
+            .transition_type(gtk::RevealerTransitionType::SlideDown)
+            .child(&exit_bar)
+            .build();
+
+        let error_page = adw::StatusPage::builder()
+            .icon_name("network-error-symbolic")
+            .child(
+                &Button::builder()
+                    .label("Retry")
+                    .action_name("remote-pane.reconnect")
+                    .halign(gtk::Align::Center)
+                    .css_classes(["pill", "suggested-action"])
+                    .build(),
+            )
+            .build();
+
+        let stack = Stack::builder().hexpand(true).vexpand(true).build();
+
+        let reconnect_label = Label::new(None);
+
+        let reconnect_overlay = Box::builder()
+            .spacing(12)
+            .halign(gtk::Align::Center)
+            .valign(gtk::Align::Center)
+            .visible(false)
+            .css_classes(["osd", "toolbar"])
+            .build();
+        reconnect_overlay.append(&gtk::Spinner::builder().spinning(true).build());
+        reconnect_overlay.append(&reconnect_label);
+        reconnect_overlay.append(
+            &Button::builder()
+                .label("Cancel")
+                .action_name("remote-pane.cancel-reconnect")
+                .build(),
+        );
+
         Self {
             term,
             server_addr: OnceCell::new(),
             server_port: OnceCell::new(),
             title: RefCell::new(String::from("Not Connected")),
-            thread_handle: RefCell::new(None),
-            size: RefCell::new((-1, -1)),
-            sender: OnceCell::new(),
+            stderr_color: RefCell::new(None),
+            state: Cell::new(ConnectionState::default()),
+            bytes_sent: Cell::new(0),
+            bytes_received: Cell::new(0),
+            secrets: RefCell::new(HashMap::new()),
+            max_reconnect_attempts: Cell::new(5),
+            stack,
+            error_page,
+            exit_banner,
+            exit_label,
+            reconnect_overlay,
+            reconnect_label,
+            reconnect_attempt: Cell::new(0),
+            reconnect_countdown: Cell::new(0),
+            reconnect_source: RefCell::new(None),
+            last_end: RefCell::new(None),
+            size_sender: RefCell::new(None),
+            resize_source: RefCell::new(None),
+            options: RefCell::new(SessionOptions::default()),
+            connect: RefCell::new(ConnectOptions::new(String::new(), 0)),
+            sender: RefCell::new(None),
         }
     }
 }
@@ -72,7 +219,15 @@ impl ObjectSubclass for RemotePane {
     type ParentType = gtk::Widget;
 
     fn class_init(klass: &mut Self::Class) {
-        klass.set_layout_manager_type::<gtk::BinLayout>();
+        klass.install_action("remote-pane.reconnect", None, |obj, _, _| {
+            if let Err(e) = obj.imp().reconnect() {
+                obj.imp().spawn_failed(e);
+            }
+        });
+
+        klass.install_action("remote-pane.cancel-reconnect", None, |obj, _, _| {
+            obj.imp().cancel_reconnect();
+        });
     }
 }
 
@@ -81,61 +236,53 @@ impl ObjectImpl for RemotePane {
     fn constructed(&self) {
         self.parent_constructed();
         let obj = &*self.obj();
-        let content = Box::builder().hexpand(true).vexpand(true).build();
-        content.append(&self.term);
+        let content = Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .hexpand(true)
+            .vexpand(true)
+            .build();
+        content.append(&self.exit_banner);
+
+        let overlay = Overlay::builder().child(&self.term).build();
+        overlay.add_overlay(&self.reconnect_overlay);
+        self.stack.add_named(&overlay, Some("terminal"));
+        self.stack.add_named(&self.error_page, Some("error"));
+        content.append(&self.stack);
+
         content.set_parent(obj);
 
+        self.term.connect_notify_local(
+            Some("window-title"),
+            clone!(@weak obj => move |_, _| {
+                obj.imp().update_title();
+            }),
+        );
+        self.update_title();
+
+        // A new font changes the number of cells without changing the allocation.
         self.term
-            .bind_property("window-title", obj, "title")
-            .transform_to(|bindings, term_title: String| {
-                let server_addr = bindings
-                    .target()
-                    .map(|s| s.property::<String>("server_addr"));
-
-                if term_title.is_empty() {
-                    server_addr
-                } else {
-                    server_addr.map(|s| format!("{} - {}", term_title, s))
-                }
-            })
-            .sync_create()
-            .build();
+            .connect_char_size_changed(clone!(@weak obj => move |_, _, _| {
+                obj.imp().schedule_resize();
+            }));
+    }
 
-        let (sender, receiver) = mpsc::channel(10);
-        {
-            let term_size = self.size.clone();
-            let sender = sender.clone();
-            self.term.connect_contents_changed(move |term| {
-                let mut term_size = term_size.borrow_mut();
-                if let Some(new_size) = term.pty().and_then(|pty| pty.size().ok()) {
-                    if new_size != *term_size {
-                        *term_size = new_size;
-                        if let Err(e) =
-                            sender.try_send(RemotePaneMsg::SizeChanged(new_size.1, new_size.0))
-                        {
-                            warn!("failed to send size data to remote : {}", e);
-                        }
-                    }
-                }
-            });
+    fn dispose(&self) {
+        if let Some(source) = self.reconnect_source.take() {
+            source.remove();
         }
 
-        self.sender.set(sender).unwrap();
-
-        if let Err(e) = self.spawn_ssh_session(receiver) {
-            error!("failed to spawn ssh session : {}", e);
+        if let Some(source) = self.resize_source.take() {
+            source.remove();
         }
-    }
+        self.size_sender.take();
 
-    fn dispose(&self) {
-        if let Some(sender) = self.sender.get() {
-            if let Err(e) = sender.blocking_send(RemotePaneMsg::Close) {
+        if let Some(sender) = self.sender.take() {
+            // The session disconnects in the background, within its own timeout, and the
+            // application waits for it when quitting. Dropping the sender closes the session
+            // too if the queue is full.
+            if let Err(e) = sender.try_send(Control::Close) {
                 warn!("failed to send close event : {}", e);
             }
-
-            if let Some(handle) = self.thread_handle.take() {
-                handle.join().unwrap();
-            }
         }
 
         while let Some(child) = self.obj().first_child() {
@@ -144,10 +291,111 @@ impl ObjectImpl for RemotePane {
     }
 }
 
-impl WidgetImpl for RemotePane {}
+impl WidgetImpl for RemotePane {
+    fn measure(&self, orientation: gtk::Orientation, for_size: i32) -> (i32, i32, i32, i32) {
+        match self.obj().first_child() {
+            Some(child) => child.measure(orientation, for_size),
+            None => (0, 0, -1, -1),
+        }
+    }
+
+    // The widget lays out its child itself instead of using a `BinLayout`, as the size
+    // allocation is the only reliable notification of the terminal being resized.
+    fn size_allocate(&self, width: i32, height: i32, baseline: i32) {
+        if let Some(child) = self.obj().first_child() {
+            child.allocate(width, height, baseline, None);
+        }
+
+        self.schedule_resize();
+    }
+}
 
 impl RemotePane {
-    fn spawn_ssh_session(&self, receiver: mpsc::Receiver<RemotePaneMsg>) -> anyhow::Result<()> {
+    pub(super) fn set_appearance(
+        &self,
+        font: Option<&gtk::pango::FontDescription>,
+        foreground: Option<&gdk::RGBA>,
+        background: Option<&gdk::RGBA>,
+    ) {
+        if font.is_some() {
+            self.term.set_font(font);
+        }
+        if let Some(foreground) = foreground {
+            self.term.set_color_foreground(foreground);
+        }
+        if let Some(background) = background {
+            self.term.set_color_background(background);
+        }
+    }
+
+    pub(super) fn start(&self) {
+        if let Err(e) = self.spawn_ssh_session() {
+            self.spawn_failed(e);
+        }
+    }
+
+    fn set_state(&self, state: ConnectionState) {
+        if self.state.replace(state) != state {
+            self.obj().notify_state();
+            self.update_title();
+        }
+    }
+
+    fn update_title(&self) {
+        let server_addr = self.server_addr.get().cloned().unwrap_or_default();
+        let term_title = self.term.window_title().unwrap_or_default();
+
+        let title = match self.state.get() {
+            ConnectionState::Connected if !term_title.is_empty() => {
+                format!("{} - {}", term_title, server_addr)
+            }
+            ConnectionState::Connected => server_addr,
+            state => format!("{} ({})", server_addr, state.label()),
+        };
+
+        self.obj().set_title(title);
+    }
+
+    /// Send the terminal size to the session once the size stopped changing for a moment.
+    fn schedule_resize(&self) {
+        // Every change restarts the delay, the callback forgets its source before it runs out.
+        if let Some(source) = self.resize_source.take() {
+            source.remove();
+        }
+
+        let obj = &*self.obj();
+        let source = glib::timeout_add_local_once(
+            RESIZE_DEBOUNCE,
+            clone!(@weak obj => move || {
+                let imp = obj.imp();
+                imp.resize_source.take();
+
+                if let Some(size_sender) = imp.size_sender.borrow().as_ref() {
+                    let size = imp.term_size();
+                    size_sender.send_if_modified(|current| {
+                        let modified = *current != size;
+                        *current = size;
+                        modified
+                    });
+                }
+            }),
+        );
+        self.resize_source.replace(Some(source));
+    }
+
+    fn term_size(&self) -> TermSize {
+        let columns = self.term.column_count().max(0) as u32;
+        let rows = self.term.row_count().max(0) as u32;
+
+        TermSize {
+            columns,
+            rows,
+            pixel_width: columns * self.term.char_width().max(0) as u32,
+            pixel_height: rows * self.term.char_height().max(0) as u32,
+        }
+    }
+
+    fn spawn_ssh_session(&self) -> anyhow::Result<()> {
         let addr = self
             .server_addr
             .get()
@@ -158,22 +406,317 @@ impl RemotePane {
             .get()
             .ok_or(anyhow::Error::msg("missing server-port"))?;
 
-        let addr_with_port = format!("{}:{}", addr, port);
+        let host = addr.clone();
+        let port = *port as u16;
 
-        let (master_pty, slave_pty) = crate::util::open_pty().context("Failed to open pty")?;
+        let (master_pty, slave_pty) =
+            flatline_session::pty::open_pty().context("Failed to open pty")?;
         let vte_pty = Pty::foreign_sync(master_pty, None::<&gio::Cancellable>)?;
         self.term.set_pty(Some(&vte_pty));
+        let (size_sender, size_receiver) = watch::channel(self.term_size());
+        self.size_sender.replace(Some(size_sender));
+        self.set_state(ConnectionState::Resolving);
+
+        let (sender, receiver) = mpsc::channel(10);
+
+        let options = SessionOptions {
+            stderr_color: self.stderr_color.borrow().as_ref().map(|color| {
+                (
+                    (color.red() * 255.0).round() as u8,
+                    (color.green() * 255.0).round() as u8,
+                    (color.blue() * 255.0).round() as u8,
+                )
+            }),
+            ..self.options.borrow().clone()
+        };
 
-        let handle = std::thread::spawn(move || {
-            let rt = tokio::runtime::Builder::new_current_thread()
-                .enable_all()
-                .build()
-                .unwrap();
+        let (events_sender, mut events) = mpsc::unbounded_channel();
+
+        let app = Application::get();
+        let handle = app.runtime().spawn(flatline_session::run_terminal(
+            ConnectOptions {
+                host,
+                port,
+                ..self.connect.borrow().clone()
+            },
+            slave_pty,
+            receiver,
+            size_receiver,
+            events_sender,
+            options,
+        ));
+        app.track_session(&sender, handle);
+        self.sender.replace(Some(sender));
+
+        // Only keep a weak reference while waiting, so closing the pane still disposes it.
+        let weak = self.obj().downgrade();
+        glib::MainContext::default().spawn_local(async move {
+            let mut finished = false;
+
+            while let Some(event) = events.recv().await {
+                let Some(obj) = weak.upgrade() else {
+                    return;
+                };
+
+                finished |= matches!(event, SessionEvent::Finished(_));
+                obj.imp().handle_event(event);
+            }
 
-            rt.block_on(crate::ssh::ssh(addr_with_port, slave_pty, receiver));
+            if let Some(obj) = weak.upgrade() {
+                if !finished {
+                    obj.imp().session_failed();
+                }
+            }
         });
-        self.thread_handle.set(Some(handle));
 
         Ok(())
     }
+
+    fn handle_event(&self, event: SessionEvent) {
+        match event {
+            SessionEvent::StateChanged(state) => self.set_state(state.into()),
+            SessionEvent::Banner(banner) => self.feed_notice(&banner),
+            SessionEvent::HostKey {
+                host,
+                port,
+                algorithm,
+                fingerprint,
+                status,
+                reply,
+            } => {
+                let obj = &*self.obj();
+                glib::MainContext::default().spawn_local(clone!(@weak obj => async move {
+                    let decision = super::prompt::ask_host_key(
+                        obj.upcast_ref(),
+                        &host,
+                        port,
+                        &algorithm,
+                        &fingerprint,
+                        status,
+                    )
+                    .await;
+                    reply.send(decision);
+                }));
+            }
+            SessionEvent::Prompt { prompt, reply } => self.ask_secret(prompt, reply),
+            SessionEvent::EnvRejected(names) => self.feed_notice(&format!(
+                "\x1b[33mflatline: the server rejected the environment variables {}\x1b[0m\n",
+                names.join(", ")
+            )),
+            SessionEvent::Exit(end) => trace!("remote process ended : {}", end),
+            SessionEvent::Stats(stats) => {
+                self.bytes_sent.set(stats.bytes_sent);
+                self.bytes_received.set(stats.bytes_received);
+                self.obj().notify_bytes_sent();
+                self.obj().notify_bytes_received();
+            }
+            SessionEvent::Finished(std::result::Result::Ok(end)) => self.session_ended(end),
+            SessionEvent::Finished(Err(e)) => self.session_error(e),
+        }
+    }
+
+    /// Print a message from flatline or the server in the terminal.
+    fn feed_notice(&self, notice: &str) {
+        let notice = notice.replace("\r\n", "\n").replace('\n', "\r\n");
+        self.term.feed(notice.as_bytes());
+    }
+
+    /// Ask the user for a secret, unless one was typed for an earlier connection.
+    fn ask_secret(&self, prompt: AuthPrompt, reply: Reply<Option<String>>) {
+        let (kind, attempt) = match &prompt {
+            AuthPrompt::Password { user, attempt } => {
+                (SecretKind::Password { user: user.clone() }, *attempt)
+            }
+            AuthPrompt::Passphrase { path, attempt } => {
+                (SecretKind::Passphrase { path: path.clone() }, *attempt)
+            }
+        };
+
+        // Another attempt means the secret given for the previous one was refused.
+        if attempt == 0 {
+            if let Some(secret) = self.secrets.borrow().get(&kind) {
+                reply.send(Some(secret.clone()));
+                return;
+            }
+        } else {
+            self.secrets.borrow_mut().remove(&kind);
+        }
+
+        let host = self.server_addr.get().cloned().unwrap_or_default();
+        let obj = &*self.obj();
+        glib::MainContext::default().spawn_local(clone!(@weak obj => async move {
+            let parent = obj.upcast_ref();
+            let secret = match prompt {
+                AuthPrompt::Password { user, attempt } => {
+                    super::prompt::ask_password(parent, &host, &user, attempt).await
+                }
+                AuthPrompt::Passphrase { path, attempt } => {
+                    super::prompt::ask_passphrase(parent, &path, attempt).await
+                }
+            };
+
+            if let Some(secret) = &secret {
+                obj.imp().secrets.borrow_mut().insert(kind, secret.clone());
+            }
+            reply.send(secret);
+        }));
+    }
+
+    fn finish_session(&self) {
+        self.sender.take();
+        self.size_sender.take();
+    }
+
+    fn session_ended(&self, end: SessionEnd) {
+        trace!("session ended : {}", end);
+        self.finish_session();
+        self.set_state(ConnectionState::Disconnected);
+
+        if end.is_unexpected() {
+            // The session was up, so the next drop gets a fresh set of attempts.
+            self.reconnect_attempt.set(0);
+            self.last_end.replace(Some(end.clone()));
+
+            if self.schedule_reconnect() {
+                return;
+            }
+        }
+
+        self.show_exit(end);
+    }
+
+    fn session_error(&self, error: Error) {
+        error!("session failed : {}", error);
+        self.finish_session();
+        self.set_state(ConnectionState::Disconnected);
+
+        if matches!(error, Error::Auth { .. }) {
+            self.secrets.borrow_mut().clear();
+        }
+
+        // A refused or cancelled authentication would only prompt again on every attempt.
+        if error.is_transient() && self.reconnect_attempt.get() > 0 && self.schedule_reconnect() {
+            return;
+        }
+
+        self.show_error(error.title(), &error.to_string());
+    }
+
+    /// The session couldn't be started at all, e.g. no pty could be opened.
+    fn spawn_failed(&self, error: anyhow::Error) {
+        error!("failed to spawn ssh session : {:#}", error);
+        self.finish_session();
+        self.show_error("Failed to Start Session", &format!("{:#}", error));
+    }
+
+    fn show_error(&self, title: &str, description: &str) {
+        self.reconnect_attempt.set(0);
+        self.last_end.replace(None);
+
+        self.error_page.set_title(title);
+        self.error_page
+            .set_description(Some(&glib::markup_escape_text(description)));
+        self.stack.set_visible_child(&self.error_page);
+
+        self.set_state(ConnectionState::Failed);
+    }
+
+    /// The session task stopped without reporting how the session ended, which only happens if it
+    /// panicked : failures to connect are reported as [`SessionEvent::Finished`] errors.
+    fn session_failed(&self) {
+        trace!("session failed");
+        self.finish_session();
+        self.set_state(ConnectionState::Disconnected);
+
+        if self.reconnect_attempt.get() > 0 && self.schedule_reconnect() {
+            return;
+        }
+
+        let end = self.last_end.take().unwrap_or(SessionEnd::ConnectionLost);
+        self.show_exit(end);
+    }
+
+    fn show_exit(&self, end: SessionEnd) {
+        self.reconnect_attempt.set(0);
+        self.last_end.replace(None);
+
+        self.exit_label.set_label(&end.to_string());
+        self.exit_banner.set_reveal_child(true);
+
+        self.set_state(ConnectionState::Disconnected);
+    }
+
+    /// Start the countdown to the next reconnection attempt, returns `false` once the attempts are exhausted.
+    fn schedule_reconnect(&self) -> bool {
+        let attempt = self.reconnect_attempt.get();
+        if attempt >= self.max_reconnect_attempts.get() {
+            return false;
+        }
+        self.reconnect_attempt.set(attempt + 1);
+
+        let delay = 1u32
+            .checked_shl(attempt)
+            .unwrap_or(MAX_RECONNECT_DELAY)
+            .min(MAX_RECONNECT_DELAY);
+        self.reconnect_countdown.set(delay);
+        self.update_reconnect_label();
+        self.reconnect_overlay.set_visible(true);
+
+        let obj = &*self.obj();
+        let source = glib::timeout_add_local(
+            Duration::from_secs(1),
+            clone!(@weak obj => @default-return glib::ControlFlow::Break, move || {
+                let imp = obj.imp();
+                let remaining = imp.reconnect_countdown.get().saturating_sub(1);
+                imp.reconnect_countdown.set(remaining);
+
+                if remaining > 0 {
+                    imp.update_reconnect_label();
+                    return glib::ControlFlow::Continue;
+                }
+
+                imp.reconnect_source.take();
+                imp.reconnect_overlay.set_visible(false);
+
+                trace!("reconnection attempt {}", imp.reconnect_attempt.get());
+                if let Err(e) = imp.spawn_ssh_session() {
+                    imp.spawn_failed(e);
+                }
+
+                glib::ControlFlow::Break
+            }),
+        );
+        self.reconnect_source.replace(Some(source));
+
+        true
+    }
+
+    fn update_reconnect_label(&self) {
+        self.reconnect_label.set_label(&format!(
+            "Reconnecting in {}s…",
+            self.reconnect_countdown.get()
+        ));
+    }
+
+    fn cancel_reconnect(&self) {
+        if let Some(source) = self.reconnect_source.take() {
+            source.remove();
+            self.reconnect_overlay.set_visible(false);
+
+            let end = self.last_end.take().unwrap_or(SessionEnd::ConnectionLost);
+            self.show_exit(end);
+        }
+    }
+
+    fn reconnect(&self) -> anyhow::Result<()> {
+        if self.sender.borrow().is_some() || self.reconnect_source.borrow().is_some() {
+            return Ok(());
+        }
+
+        self.exit_banner.set_reveal_child(false);
+        self.stack.set_visible_child_name("terminal");
+        self.term.reset(true, false);
+
+        self.spawn_ssh_session()
+    }
 }
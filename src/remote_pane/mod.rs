@@ -1,7 +1,12 @@
-use glib::Object;
-use gtk::gdk;
+use flatline_session::{AuthMethod, ConnectOptions, SessionOptions};
+use glib::{subclass::types::ObjectSubclassIsExt, Object};
+use gtk::{gdk, pango};
 
 pub mod imp;
+mod prompt;
+mod state;
+
+pub use state::ConnectionState;
 
 glib::wrapper! {
     pub struct RemotePane(ObjectSubclass<imp::RemotePane>)
@@ -17,142 +22,179 @@ impl RemotePane {
     pub fn builder() -> RemotePaneBuilder {
         RemotePaneBuilder::new()
     }
+
+    /// Connect to the server, neither constructor does it so the pane can be placed first.
+    pub fn start(&self) {
+        self.imp().start();
+    }
 }
 
 pub struct RemotePaneBuilder {
     builder: glib::object::ObjectBuilder<'static, RemotePane>,
+    options: SessionOptions,
+    /// Host and port are taken from the properties.
+    connect: ConnectOptions,
+    font: Option<pango::FontDescription>,
+    foreground: Option<gdk::RGBA>,
+    background: Option<gdk::RGBA>,
 }
 
 impl RemotePaneBuilder {
     fn new() -> Self {
         Self {
             builder: glib::object::Object::builder(),
+            options: SessionOptions::default(),
+            connect: ConnectOptions::new(String::new(), 0),
+            font: None,
+            foreground: None,
+            background: None,
         }
     }
 
     pub fn can_focus(self, can_focus: bool) -> Self {
         Self {
             builder: self.builder.property("can-focus", can_focus),
+            ..self
         }
     }
 
     pub fn can_target(self, can_target: bool) -> Self {
         Self {
             builder: self.builder.property("can-target", can_target),
+            ..self
         }
     }
 
     pub fn css_classes(self, css_classes: impl Into<glib::StrV>) -> Self {
         Self {
             builder: self.builder.property("css-classes", css_classes.into()),
+            ..self
         }
     }
 
     pub fn css_name(self, css_name: impl Into<glib::GString>) -> Self {
         Self {
             builder: self.builder.property("css-name", css_name.into()),
+            ..self
         }
     }
 
     pub fn cursor(self, cursor: &gdk::Cursor) -> Self {
         Self {
             builder: self.builder.property("cursor", cursor.clone()),
+            ..self
         }
     }
 
     pub fn focus_on_click(self, focus_on_click: bool) -> Self {
         Self {
             builder: self.builder.property("focus-on-click", focus_on_click),
+            ..self
         }
     }
 
     pub fn focusable(self, focusable: bool) -> Self {
         Self {
             builder: self.builder.property("focusable", focusable),
+            ..self
         }
     }
 
     pub fn halign(self, halign: gtk::Align) -> Self {
         Self {
             builder: self.builder.property("halign", halign),
+            ..self
         }
     }
 
     pub fn has_tooltip(self, has_tooltip: bool) -> Self {
         Self {
             builder: self.builder.property("has-tooltip", has_tooltip),
+            ..self
         }
     }
 
     pub fn height_request(self, height_request: i32) -> Self {
         Self {
             builder: self.builder.property("height-request", height_request),
+            ..self
         }
     }
 
     pub fn hexpand(self, hexpand: bool) -> Self {
         Self {
             builder: self.builder.property("hexpand", hexpand),
+            ..self
         }
     }
 
     pub fn hexpand_set(self, hexpand_set: bool) -> Self {
         Self {
             builder: self.builder.property("hexpand-set", hexpand_set),
+            ..self
         }
     }
 
     pub fn margin_bottom(self, margin_bottom: i32) -> Self {
         Self {
             builder: self.builder.property("margin-bottom", margin_bottom),
+            ..self
         }
     }
 
     pub fn margin_end(self, margin_end: i32) -> Self {
         Self {
             builder: self.builder.property("margin-end", margin_end),
+            ..self
         }
     }
 
     pub fn margin_start(self, margin_start: i32) -> Self {
         Self {
             builder: self.builder.property("margin-start", margin_start),
+            ..self
         }
     }
 
     pub fn margin_top(self, margin_top: i32) -> Self {
         Self {
             builder: self.builder.property("margin-top", margin_top),
+            ..self
         }
     }
 
     pub fn name(self, name: impl Into<glib::GString>) -> Self {
         Self {
             builder: self.builder.property("name", name.into()),
+            ..self
         }
     }
 
     pub fn opacity(self, opacity: f64) -> Self {
         Self {
             builder: self.builder.property("opacity", opacity),
+            ..self
         }
     }
 
     pub fn overflow(self, overflow: gtk::Overflow) -> Self {
         Self {
             builder: self.builder.property("overflow", overflow),
+            ..self
         }
     }
 
     pub fn receives_default(self, receives_default: bool) -> Self {
         Self {
             builder: self.builder.property("receives-default", receives_default),
+            ..self
         }
     }
 
     pub fn sensitive(self, sensitive: bool) -> Self {
         Self {
             builder: self.builder.property("sensitive", sensitive),
+            ..self
         }
     }
 
@@ -161,42 +203,49 @@ impl RemotePaneBuilder {
             builder: self
                 .builder
                 .property("tooltip-markup", tooltip_markup.into()),
+            ..self
         }
     }
 
     pub fn tooltip_text(self, tooltip_text: impl Into<glib::GString>) -> Self {
         Self {
             builder: self.builder.property("tooltip-text", tooltip_text.into()),
+            ..self
         }
     }
 
     pub fn valign(self, valign: gtk::Align) -> Self {
         Self {
             builder: self.builder.property("valign", valign),
+            ..self
         }
     }
 
     pub fn vexpand(self, vexpand: bool) -> Self {
         Self {
             builder: self.builder.property("vexpand", vexpand),
+            ..self
         }
     }
 
     pub fn vexpand_set(self, vexpand_set: bool) -> Self {
         Self {
             builder: self.builder.property("vexpand-set", vexpand_set),
+            ..self
         }
     }
 
     pub fn visible(self, visible: bool) -> Self {
         Self {
             builder: self.builder.property("visible", visible),
+            ..self
         }
     }
 
     pub fn width_request(self, width_request: i32) -> Self {
         Self {
             builder: self.builder.property("width-request", width_request),
+            ..self
         }
     }
 
@@ -208,17 +257,109 @@ impl RemotePaneBuilder {
 
         Self {
             builder: self.builder.property("server-addr", addr),
+            ..self
         }
     }
 
     pub fn server_port(self, port: u16) -> Self {
         Self {
             builder: self.builder.property("server-port", port as u32),
+            ..self
+        }
+    }
+
+    pub fn max_reconnect_attempts(self, attempts: u32) -> Self {
+        Self {
+            builder: self.builder.property("max-reconnect-attempts", attempts),
+            ..self
+        }
+    }
+
+    pub fn stderr_color(self, color: &gdk::RGBA) -> Self {
+        Self {
+            builder: self.builder.property("stderr-color", Some(color)),
+            ..self
+        }
+    }
+
+    pub fn term(mut self, term: impl Into<String>) -> Self {
+        self.options.term = term.into();
+        self
+    }
+
+    pub fn env(mut self, env: Vec<(String, String)>) -> Self {
+        self.options.env = env;
+        self
+    }
+
+    pub fn send_env(mut self, send_env: Vec<String>) -> Self {
+        self.options.send_env = send_env;
+        self
+    }
+
+    pub fn keepalive(mut self, interval: Option<std::time::Duration>, count_max: u32) -> Self {
+        self.options.keepalive_interval = interval;
+        self.options.keepalive_count_max = count_max;
+        self
+    }
+
+    pub fn terminal_modes(mut self, modes: Vec<(flatline_session::Pty, u32)>) -> Self {
+        self.options.terminal_modes = modes;
+        self
+    }
+
+    pub fn user(mut self, user: impl Into<String>) -> Self {
+        self.connect.user = Some(user.into());
+        self
+    }
+
+    pub fn auth(mut self, auth: AuthMethod) -> Self {
+        self.connect.auth = auth;
+        self
+    }
+
+    pub fn jump_hosts(mut self, jump_hosts: Vec<ConnectOptions>) -> Self {
+        self.connect.jump_hosts = jump_hosts;
+        self
+    }
+
+    pub fn proxy_command(mut self, proxy_command: impl Into<String>) -> Self {
+        self.connect.proxy_command = Some(proxy_command.into());
+        self
+    }
+
+    pub fn font(self, font: &pango::FontDescription) -> Self {
+        Self {
+            font: Some(font.clone()),
+            ..self
+        }
+    }
+
+    pub fn foreground_color(self, color: &gdk::RGBA) -> Self {
+        Self {
+            foreground: Some(*color),
+            ..self
+        }
+    }
+
+    pub fn background_color(self, color: &gdk::RGBA) -> Self {
+        Self {
+            background: Some(*color),
+            ..self
         }
     }
 
     #[must_use = "Building the object from the builder is usually expensive and is not expected to have side effects"]
     pub fn build(self) -> RemotePane {
-        self.builder.build()
+        let remote_pane = self.builder.build();
+        let imp = remote_pane.imp();
+        imp.set_appearance(
+            self.font.as_ref(),
+            self.foreground.as_ref(),
+            self.background.as_ref(),
+        );
+        imp.options.replace(self.options);
+        imp.connect.replace(self.connect);
+        remote_pane
     }
 }
@@ -0,0 +1,110 @@
+use std::path::Path;
+
+use adw::prelude::*;
+
+use flatline_session::{HostKeyDecision, HostKeyStatus};
+
+fn message_dialog(parent: &gtk::Widget, heading: &str, body: &str) -> adw::MessageDialog {
+    let window = parent.root().and_downcast::<gtk::Window>();
+    adw::MessageDialog::new(window.as_ref(), Some(heading), Some(body))
+}
+
+/// Ask the user whether to trust the host key offered by `host`.
+pub(super) async fn ask_host_key(
+    parent: &gtk::Widget,
+    host: &str,
+    port: u16,
+    algorithm: &str,
+    fingerprint: &str,
+    status: HostKeyStatus,
+) -> HostKeyDecision {
+    let (heading, body) = match status {
+        HostKeyStatus::Unknown => (
+            "Unknown Host",
+            format!(
+                "The authenticity of {}:{} can't be established.\n\n{} key fingerprint is\nSHA256:{}",
+                host, port, algorithm, fingerprint
+            ),
+        ),
+        HostKeyStatus::Changed { line } => (
+            "Host Key Changed",
+            format!(
+                "The host key of {}:{} does not match the one in known_hosts at line {}. Someone could be eavesdropping on you.\n\n{} key fingerprint is\nSHA256:{}",
+                host, port, line, algorithm, fingerprint
+            ),
+        ),
+    };
+
+    let dialog = message_dialog(parent, heading, &body);
+    dialog.add_responses(&[("reject", "_Reject"), ("once", "Accept _Once")]);
+    dialog.set_close_response("reject");
+    dialog.set_default_response(Some("reject"));
+
+    match status {
+        HostKeyStatus::Unknown => {
+            dialog.add_response("save", "_Accept and Save");
+            dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+        }
+        HostKeyStatus::Changed { .. } => {
+            dialog.set_response_appearance("once", adw::ResponseAppearance::Destructive);
+        }
+    }
+
+    match dialog.choose_future().await.as_str() {
+        "save" => HostKeyDecision::AcceptAndSave,
+        "once" => HostKeyDecision::AcceptOnce,
+        _ => HostKeyDecision::Reject,
+    }
+}
+
+/// Ask the user for the password of `user`, `None` if the dialog was cancelled.
+pub(super) async fn ask_password(
+    parent: &gtk::Widget,
+    host: &str,
+    user: &str,
+    attempt: u32,
+) -> Option<String> {
+    let body = if attempt == 0 {
+        format!("Enter the password of {}@{}", user, host)
+    } else {
+        format!("Wrong password, try again for {}@{}", user, host)
+    };
+
+    ask_secret(parent, "Password Required", &body).await
+}
+
+/// Ask the user for the passphrase of the key at `path`, `None` if the dialog was cancelled.
+pub(super) async fn ask_passphrase(
+    parent: &gtk::Widget,
+    path: &Path,
+    attempt: u32,
+) -> Option<String> {
+    let body = if attempt == 0 {
+        format!("Enter the passphrase of {}", path.display())
+    } else {
+        format!("Wrong passphrase, try again for {}", path.display())
+    };
+
+    ask_secret(parent, "Passphrase Required", &body).await
+}
+
+async fn ask_secret(parent: &gtk::Widget, heading: &str, body: &str) -> Option<String> {
+    let dialog = message_dialog(parent, heading, body);
+
+    let entry = gtk::PasswordEntry::builder()
+        .show_peek_icon(true)
+        .activates_default(true)
+        .build();
+    dialog.set_extra_child(Some(&entry));
+
+    dialog.add_responses(&[("cancel", "_Cancel"), ("login", "_Log In")]);
+    dialog.set_response_appearance("login", adw::ResponseAppearance::Suggested);
+    dialog.set_close_response("cancel");
+    dialog.set_default_response(Some("login"));
+
+    if dialog.choose_future().await == "login" {
+        Some(entry.text().to_string())
+    } else {
+        None
+    }
+}
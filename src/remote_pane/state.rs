@@ -0,0 +1,69 @@
+use flatline_session::SessionState;
+
+/// Connection state of a remote pane, as shown on its tab.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "FlatLineConnectionState")]
+pub enum ConnectionState {
+    Resolving,
+    Connecting,
+    VerifyingHost,
+    Authenticating,
+    Connected,
+    #[default]
+    Disconnected,
+    Failed,
+}
+
+impl ConnectionState {
+    /// Whether the connection is being established.
+    pub fn is_loading(self) -> bool {
+        matches!(
+            self,
+            ConnectionState::Resolving
+                | ConnectionState::Connecting
+                | ConnectionState::VerifyingHost
+                | ConnectionState::Authenticating
+        )
+    }
+
+    /// Whether the user should look at the pane.
+    pub fn needs_attention(self) -> bool {
+        matches!(
+            self,
+            ConnectionState::Disconnected | ConnectionState::Failed
+        )
+    }
+
+    /// Icon shown on the tab, if any.
+    pub fn icon_name(self) -> Option<&'static str> {
+        match self {
+            ConnectionState::Disconnected => Some("network-offline-symbolic"),
+            ConnectionState::Failed => Some("dialog-error-symbolic"),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ConnectionState::Resolving => "Resolving",
+            ConnectionState::Connecting => "Connecting",
+            ConnectionState::VerifyingHost => "Verifying Host",
+            ConnectionState::Authenticating => "Authenticating",
+            ConnectionState::Connected => "Connected",
+            ConnectionState::Disconnected => "Not Connected",
+            ConnectionState::Failed => "Failed",
+        }
+    }
+}
+
+impl From<SessionState> for ConnectionState {
+    fn from(state: SessionState) -> Self {
+        match state {
+            SessionState::Resolving => ConnectionState::Resolving,
+            SessionState::Connecting => ConnectionState::Connecting,
+            SessionState::VerifyingHost => ConnectionState::VerifyingHost,
+            SessionState::Authenticating => ConnectionState::Authenticating,
+            SessionState::Connected => ConnectionState::Connected,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, trace};
+
+/// What happens when the last tab of a window is closed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LastTabPolicy {
+    /// Close the window along with its last tab.
+    #[default]
+    CloseWindow,
+    /// Replace the last tab with a new-pane page.
+    KeepNewPane,
+}
+
+/// Application settings, edited by hand in `settings.toml`, such as :
+///
+/// ```toml
+/// # What closing the last tab of a window does : "close-window", the default, or
+/// # "keep-new-pane" to leave a new-pane page in its place.
+/// last-tab = "keep-new-pane"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Settings {
+    /// `last-tab`, see [`LastTabPolicy`].
+    pub last_tab: LastTabPolicy,
+}
+
+impl Settings {
+    /// Default location of the settings, under `$XDG_CONFIG_HOME/flatline`.
+    pub fn default_path() -> PathBuf {
+        glib::user_config_dir()
+            .join("flatline")
+            .join("settings.toml")
+    }
+
+    /// Read the settings at `path`, the defaults are used if it can't be read.
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).unwrap_or_else(|e| {
+                error!("failed to read {} : {}", path.display(), e);
+                Self::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                trace!("no settings at {}", path.display());
+                Self::default()
+            }
+            Err(e) => {
+                error!("failed to read {} : {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+}
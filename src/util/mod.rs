@@ -1,3 +0,0 @@
-mod posix;
-
-pub(crate) use posix::*;